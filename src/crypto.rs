@@ -0,0 +1,89 @@
+use crate::io::{PublicKey, Signature};
+use ed25519_dalek::{PublicKey as Ed25519PublicKey, Signature as Ed25519Signature, Verifier as _};
+use gstd::prelude::*;
+use schnorrkel::{PublicKey as Sr25519PublicKey, Signature as Sr25519Signature};
+use sha3::{Digest, Keccak256};
+
+/// Signing context used by `sp_core::sr25519` and all the Substrate tooling.
+const SR25519_SIGNING_CONTEXT: &[u8] = b"substrate";
+
+/// Prefix of the messages signed with Ethereum's `personal_sign` (EIP-191).
+const ETHEREUM_MESSAGE_PREFIX: &[u8] = b"\x19Ethereum Signed Message:\n";
+
+/// Checks that the `signature` of the `message` was made with the `public` key.
+///
+/// The key and the signature MUST belong to the same scheme,
+/// otherwise the signature is considered invalid.
+pub fn verify(public: &PublicKey, signature: &Signature, message: &[u8]) -> bool {
+    match (public, signature) {
+        (PublicKey::Sr25519(public), Signature::Sr25519(signature)) => {
+            verify_sr25519(public, signature, message)
+        }
+        (PublicKey::Ed25519(public), Signature::Ed25519(signature)) => {
+            verify_ed25519(public, signature, message)
+        }
+        (PublicKey::Secp256k1(public), Signature::Secp256k1(signature)) => {
+            verify_secp256k1(public, signature, message)
+        }
+        _ => false,
+    }
+}
+
+fn verify_sr25519(public: &[u8; 32], signature: &[u8; 64], message: &[u8]) -> bool {
+    let (public, signature) = match (
+        Sr25519PublicKey::from_bytes(public),
+        Sr25519Signature::from_bytes(signature),
+    ) {
+        (Ok(public), Ok(signature)) => (public, signature),
+        _ => return false,
+    };
+
+    public
+        .verify_simple(SR25519_SIGNING_CONTEXT, message, &signature)
+        .is_ok()
+}
+
+fn verify_ed25519(public: &[u8; 32], signature: &[u8; 64], message: &[u8]) -> bool {
+    let public = match Ed25519PublicKey::from_bytes(public) {
+        Ok(public) => public,
+        _ => return false,
+    };
+
+    public
+        .verify(message, &Ed25519Signature::from(*signature))
+        .is_ok()
+}
+
+/// Checks an Ethereum `personal_sign` signature (EIP-191):
+/// the key is recovered from the Keccak-256 hash of the prefixed `message`
+/// with the recovery id `v` (either `0`/`1` or `27`/`28`) and compared with the `public` one,
+/// which is the same as comparing their Ethereum addresses.
+fn verify_secp256k1(public: &[u8; 33], signature: &[u8; 65], message: &[u8]) -> bool {
+    let v = signature[64];
+    let (signature, recovery_id) = match (
+        libsecp256k1::Signature::parse_standard_slice(&signature[..64]),
+        libsecp256k1::RecoveryId::parse(if v >= 27 { v - 27 } else { v }),
+    ) {
+        (Ok(signature), Ok(recovery_id)) => (signature, recovery_id),
+        _ => return false,
+    };
+
+    match libsecp256k1::recover(
+        &libsecp256k1::Message::parse(&ethereum_message_hash(message)),
+        &signature,
+        &recovery_id,
+    ) {
+        Ok(recovered) => recovered.serialize_compressed() == *public,
+        Err(_) => false,
+    }
+}
+
+/// Returns the hash an Ethereum wallet signs with `personal_sign` for the `message`.
+fn ethereum_message_hash(message: &[u8]) -> [u8; 32] {
+    Keccak256::new()
+        .chain_update(ETHEREUM_MESSAGE_PREFIX)
+        .chain_update(message.len().to_string())
+        .chain_update(message)
+        .finalize()
+        .into()
+}
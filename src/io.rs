@@ -1,19 +1,867 @@
-use gstd::prelude::*;
+use blake2::{digest::consts::U32, Blake2b};
+use gstd::{prelude::*, ActorId};
+use sha2::{Digest, Sha256};
 
-/// Typings for u8 arrays.
-pub type PublicKey = [u8; 32];
-pub type Signature = [u8; 64];
 pub type PieceId = u128;
+pub type SchemaId = u128;
+pub type PolicyId = u128;
+
+/// Algorithm used to hash the claim attributes off-chain.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha256,
+    Blake2b256,
+}
+
+impl HashAlgorithm {
+    /// Returns the salted commitment `H(salt || value)` to an attribute value.
+    ///
+    /// Subjects SHOULD keep a random salt for each attribute to themselves,
+    /// so the committed values can't be brute-forced from the public hashes.
+    pub fn commitment(&self, salt: &[u8; 32], value: &[u8]) -> [u8; 32] {
+        match self {
+            Self::Sha256 => Sha256::new()
+                .chain_update(salt)
+                .chain_update(value)
+                .finalize()
+                .into(),
+            Self::Blake2b256 => Blake2b::<U32>::new()
+                .chain_update(salt)
+                .chain_update(value)
+                .finalize()
+                .into(),
+        }
+    }
+
+    /// Returns `H^length(seed)`, the `seed` hashed `length` times.
+    ///
+    /// The issuer commits to a numeric attribute `n` with `hash_chain(seed, n)`.
+    /// The subject proves `n >= k` without revealing `n` with `hash_chain(seed, n - k)`,
+    /// which can be hashed `k` more times into the commitment (see [`ThresholdProof`]).
+    pub fn hash_chain(&self, seed: &[u8; 32], length: u32) -> [u8; 32] {
+        (0..length).fold(*seed, |hash, _| match self {
+            Self::Sha256 => Sha256::digest(hash).into(),
+            Self::Blake2b256 => Blake2b::<U32>::digest(hash).into(),
+        })
+    }
+}
+
+/// Schema describes a type of claims (e.g. "KYC", "residence").
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct Schema {
+    /// Schema's id generated automatically.
+    pub id: SchemaId,
+    /// Schema's name.
+    pub name: String,
+    /// Ordered attribute names. The claim's `hashed_info` holds the hashes of the attributes in this order.
+    pub attributes: Vec<String>,
+    /// Algorithm the attributes are hashed with.
+    pub hash_algorithm: HashAlgorithm,
+}
+
+/// RelyingPartyPolicy is a set of requirements a relying party puts on the claims of a subject.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct RelyingPartyPolicy {
+    /// Policy's id generated automatically.
+    pub id: PolicyId,
+    /// Actor that registered the policy.
+    pub owner: ActorId,
+    /// Policy's name.
+    pub name: String,
+    /// Required schema of the claims.
+    pub schema_id: SchemaId,
+    /// Accepted issuers. Empty if every issuer is accepted.
+    pub issuers: Vec<PublicKey>,
+    /// Minimum number of the verifications that aren't withdrawn.
+    pub min_verifiers: u32,
+    /// Maximum time in milliseconds since the claim issuance block.
+    pub max_age: Option<u64>,
+}
+
+/// Public key tagged with its signature scheme.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PublicKey {
+    Sr25519([u8; 32]),
+    Ed25519([u8; 32]),
+    /// Compressed SEC1 public key of an Ethereum account.
+    Secp256k1([u8; 33]),
+}
+
+/// Signature tagged with its signature scheme.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Signature {
+    Sr25519([u8; 64]),
+    Ed25519([u8; 64]),
+    /// Ethereum `personal_sign` (EIP-191) signature `r || s || v`, where `v` is the recovery id.
+    Secp256k1([u8; 65]),
+}
+
+/// Lifecycle status of a claim.
+///
+/// Allowed transitions:
+/// * `Pending` -> `Active` - by the issuer or the admin.
+/// * `Active` -> `Suspended` - by the issuer, the subject or the admin.
+/// * `Suspended` -> `Active` - by the issuer or the admin.
+/// * `Pending`, `Active`, `Suspended` -> `Expired` - by the admin.
+/// * any status except `Revoked` -> `Revoked` - by the issuer with [`IdentityAction::RevokeClaim`].
+///
+/// `Revoked` and `Expired` are final.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClaimStatus {
+    /// Issued, but not activated yet.
+    Pending,
+    /// Valid claim.
+    Active,
+    /// Temporarily invalid claim.
+    Suspended,
+    /// Permanently invalid claim.
+    Revoked,
+    /// Outdated claim.
+    Expired,
+}
+
+/// Expected block duration in milliseconds used to schedule [`IdentityAction::ExpireClaim`] on issuance.
+/// The rescheduled ones use the block duration observed since the claim issuance.
+pub const BLOCK_DURATION: u64 = 3000;
+/// Gas sent with the delayed [`IdentityAction::ExpireClaim`] on issuance.
+/// What's left of it is passed on to the rescheduled ones.
+pub const EXPIRY_GAS: u64 = 10_000_000_000;
+/// Gas kept by a message scheduling [`IdentityAction::ExpireClaim`] to finish its own handling.
+/// Issuing a claim with `valid_until` requires it on top of [`EXPIRY_GAS`].
+pub const EXPIRY_HANDLING_GAS: u64 = 1_000_000_000;
+
+/// How far in milliseconds [`ClaimData::issuance_date`] can be ahead of the issuance block timestamp.
+pub const MAX_ISSUANCE_DATE_DRIFT: u64 = 5 * 60 * 1000;
+
+/// HashedInfo holds the hashes of the claim's attributes in the schema's order.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub enum HashedInfo {
+    /// Hash of each attribute (e.g. Vec::from([city], [street])).
+    List(Vec<[u8; 32]>),
+    /// [`merkle_root`] over the hashes of the attributes.
+    /// Keeps the storage constant and lets the subject reveal one attribute at a time.
+    MerkleRoot {
+        /// Root of the Merkle tree.
+        root: [u8; 32],
+        /// Number of the tree leaves.
+        leaf_count: u32,
+    },
+}
+
+impl HashedInfo {
+    /// Returns the number of the hashed attributes.
+    pub fn attribute_count(&self) -> usize {
+        match self {
+            Self::List(hashes) => hashes.len(),
+            Self::MerkleRoot { leaf_count, .. } => *leaf_count as usize,
+        }
+    }
+}
+
+fn merkle_leaf(leaf: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([0])
+        .chain_update(leaf)
+        .finalize()
+        .into()
+}
+
+fn merkle_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    Sha256::new()
+        .chain_update([1])
+        .chain_update(left)
+        .chain_update(right)
+        .finalize()
+        .into()
+}
+
+fn merkle_level(nodes: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    nodes
+        .chunks(2)
+        .map(|pair| merkle_node(&pair[0], pair.get(1).unwrap_or(&pair[0])))
+        .collect()
+}
+
+/// Returns the root of the Merkle tree over the `leaves`.
+///
+/// Leaves are hashed as `Sha256(0x00 || leaf)` and nodes as `Sha256(0x01 || left || right)`.
+/// The last node of a level with an odd number of nodes is paired with itself.
+/// The root of an empty tree is zero.
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(merkle_leaf).collect();
+    if level.is_empty() {
+        return [0; 32];
+    }
+    while level.len() > 1 {
+        level = merkle_level(&level);
+    }
+    level[0]
+}
+
+/// Returns the Merkle path of the leaf at the `index` from the bottom up.
+///
+/// # Panics
+/// If the `index` is out of the `leaves` bounds.
+pub fn merkle_path(leaves: &[[u8; 32]], index: usize) -> Vec<[u8; 32]> {
+    let mut level: Vec<[u8; 32]> = leaves.iter().map(merkle_leaf).collect();
+    let mut index = index;
+    let mut path = vec![];
+    while level.len() > 1 {
+        path.push(*level.get(index ^ 1).unwrap_or(&level[index]));
+        level = merkle_level(&level);
+        index /= 2;
+    }
+    path
+}
+
+/// Checks that the `leaf` is at the `index` of the Merkle tree with the `root` and `leaf_count` leaves.
+pub fn verify_merkle_path(
+    root: &[u8; 32],
+    leaf_count: u32,
+    index: u32,
+    leaf: &[u8; 32],
+    path: &[[u8; 32]],
+) -> bool {
+    if index >= leaf_count {
+        return false;
+    }
+    let (mut node, mut index, mut width) = (merkle_leaf(leaf), index, leaf_count);
+    let mut path = path.iter();
+    while width > 1 {
+        node = match path.next() {
+            Some(sibling) if index % 2 == 0 => merkle_node(&node, sibling),
+            Some(sibling) => merkle_node(sibling, &node),
+            None => return false,
+        };
+        index /= 2;
+        width = width / 2 + width % 2;
+    }
+    path.next().is_none() && &node == root
+}
 
 /// ClaimData represents an internal data stored inside a claim.
 #[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
 pub struct ClaimData {
-    /// Set of hashed data (e.g. Vec::from([city], [street])).
-    pub hashed_info: Vec<[u8; 32]>,
-    /// Date of issuance of this claim.
+    /// Hashed data.
+    pub hashed_info: HashedInfo,
+    /// Schema of the claim. `hashed_info` MUST hash each schema's attribute.
+    pub schema_id: SchemaId,
+    /// Date of issuance of this claim asserted by the issuer.
+    /// MUST NOT be later than [`MAX_ISSUANCE_DATE_DRIFT`] after the issuance block timestamp.
     pub issuance_date: u64,
-    /// Validation status of the claim.
-    pub valid: bool,
+    /// Status of the claim. MUST be either `Pending` or `Active` on issuance.
+    pub status: ClaimStatus,
+    /// Block timestamp the claim is valid from (inclusive).
+    pub valid_from: Option<u64>,
+    /// Block timestamp the claim is valid until (exclusive).
+    /// The claim is switched to `Expired` by [`IdentityAction::ExpireClaim`] sent on time to the program itself.
+    pub valid_until: Option<u64>,
+    /// Schemas of the claims the subject is accredited to issue by the issuer.
+    /// A claim with non-empty `accredited_types` is an accreditation of the subject as an issuer.
+    pub accredited_types: Vec<SchemaId>,
+}
+
+impl ClaimData {
+    /// Returns the status of the claim at the `timestamp` taking its validity period into account.
+    ///
+    /// A not revoked claim is `Expired` from `valid_until`
+    /// and an `Active` claim is `Pending` before `valid_from`.
+    pub fn status_at(&self, timestamp: u64) -> ClaimStatus {
+        match self.status {
+            ClaimStatus::Revoked | ClaimStatus::Expired => self.status,
+            _ if matches!(self.valid_until, Some(until) if timestamp >= until) => {
+                ClaimStatus::Expired
+            }
+            ClaimStatus::Active if matches!(self.valid_from, Some(from) if timestamp < from) => {
+                ClaimStatus::Pending
+            }
+            status => status,
+        }
+    }
+}
+
+/// IssuanceDate is the issuer's asserted date of a claim along with the block the claim was issued in.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IssuanceDate {
+    /// Date asserted by the issuer ([`ClaimData::issuance_date`]).
+    pub asserted: u64,
+    /// Timestamp of the issuance block.
+    pub block_timestamp: u64,
+    /// Height of the issuance block.
+    pub block_height: u32,
+}
+
+/// Domain tag of the [`ClaimSigningPayload`].
+pub const CLAIM_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/claim/v1";
+
+/// ClaimSigningPayload is what an issuer signs to issue a claim.
+///
+/// Binds the issuer's signature to the identity program, the subject, the issuer
+/// and the issuer's nonce, so it can't be replayed onto another subject,
+/// another deployment or the same storage once again.
+/// Off-chain signers MUST sign [`ClaimSigningPayload::signing_bytes`]
+/// of the payload built with [`ClaimSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct ClaimSigningPayload {
+    /// Domain tag. Always equals to [`CLAIM_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Issuer's public key.
+    pub issuer: PublicKey,
+    /// Claim's data.
+    pub data: ClaimData,
+    /// Issuer's current nonce.
+    pub nonce: u64,
+}
+
+impl ClaimSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        subject: PublicKey,
+        issuer: PublicKey,
+        data: ClaimData,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: CLAIM_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            subject,
+            issuer,
+            data,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`VerificationSigningPayload`].
+pub const VERIFICATION_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/verification/v1";
+
+/// VerificationSigningPayload is what a verifier signs to verify a claim.
+/// Off-chain signers MUST sign [`VerificationSigningPayload::signing_bytes`]
+/// of the payload built with [`VerificationSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct VerificationSigningPayload {
+    /// Domain tag. Always equals to [`VERIFICATION_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Verifier's public key.
+    pub verifier: PublicKey,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Claim's id.
+    pub piece_id: PieceId,
+    /// [`Claim::hash`] of the verified claim.
+    pub claim_hash: [u8; 32],
+    /// Verifier's own statement about the claim (e.g. "checked the passport in person").
+    pub statement: String,
+    /// Verifier's current nonce.
+    pub nonce: u64,
+}
+
+impl VerificationSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        verifier: PublicKey,
+        subject: PublicKey,
+        piece_id: PieceId,
+        claim_hash: [u8; 32],
+        statement: String,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: VERIFICATION_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            verifier,
+            subject,
+            piece_id,
+            claim_hash,
+            statement,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`StatusChangeSigningPayload`].
+pub const STATUS_CHANGE_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/status-change/v1";
+
+/// StatusChangeSigningPayload is what a validator signs to change a claim's validation status.
+/// Off-chain signers MUST sign [`StatusChangeSigningPayload::signing_bytes`]
+/// of the payload built with [`StatusChangeSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct StatusChangeSigningPayload {
+    /// Domain tag. Always equals to [`STATUS_CHANGE_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Validator's public key.
+    pub validator: PublicKey,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Claim's id.
+    pub piece_id: PieceId,
+    /// New status of the claim.
+    pub status: ClaimStatus,
+    /// Validator's current nonce.
+    pub nonce: u64,
+}
+
+impl StatusChangeSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        validator: PublicKey,
+        subject: PublicKey,
+        piece_id: PieceId,
+        status: ClaimStatus,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: STATUS_CHANGE_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            validator,
+            subject,
+            piece_id,
+            status,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`KeyLinkSigningPayload`].
+pub const KEY_LINK_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/key-link/v1";
+
+/// KeyLinkSigningPayload is a challenge signed to link a public key to an actor.
+/// Off-chain signers MUST sign [`KeyLinkSigningPayload::signing_bytes`]
+/// of the payload built with [`KeyLinkSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct KeyLinkSigningPayload {
+    /// Domain tag. Always equals to [`KEY_LINK_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Public key being linked.
+    pub key: PublicKey,
+    /// Actor the key is linked to.
+    pub actor: ActorId,
+    /// Key's current nonce.
+    pub nonce: u64,
+}
+
+impl KeyLinkSigningPayload {
+    pub fn new(program_id: ActorId, key: PublicKey, actor: ActorId, nonce: u64) -> Self {
+        Self {
+            domain: KEY_LINK_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            key,
+            actor,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`KeyUnlinkSigningPayload`].
+pub const KEY_UNLINK_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/key-unlink/v1";
+
+/// KeyUnlinkSigningPayload is what a key holder signs to unlink the key from an actor.
+/// Off-chain signers MUST sign [`KeyUnlinkSigningPayload::signing_bytes`]
+/// of the payload built with [`KeyUnlinkSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct KeyUnlinkSigningPayload {
+    /// Domain tag. Always equals to [`KEY_UNLINK_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Public key being unlinked.
+    pub key: PublicKey,
+    /// Actor the key is unlinked from.
+    pub actor: ActorId,
+    /// Key's current nonce.
+    pub nonce: u64,
+}
+
+impl KeyUnlinkSigningPayload {
+    pub fn new(program_id: ActorId, key: PublicKey, actor: ActorId, nonce: u64) -> Self {
+        Self {
+            domain: KEY_UNLINK_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            key,
+            actor,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`RevocationSigningPayload`].
+pub const REVOCATION_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/revocation/v1";
+
+/// RevocationSigningPayload is what an issuer signs to revoke a claim.
+/// Off-chain signers MUST sign [`RevocationSigningPayload::signing_bytes`]
+/// of the payload built with [`RevocationSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct RevocationSigningPayload {
+    /// Domain tag. Always equals to [`REVOCATION_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Issuer's public key.
+    pub issuer: PublicKey,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Claim's id.
+    pub piece_id: PieceId,
+    /// Reason of the revocation.
+    pub reason: RevocationReason,
+    /// Issuer's current nonce.
+    pub nonce: u64,
+}
+
+impl RevocationSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        issuer: PublicKey,
+        subject: PublicKey,
+        piece_id: PieceId,
+        reason: RevocationReason,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: REVOCATION_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            issuer,
+            subject,
+            piece_id,
+            reason,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Reason code of a claim revocation.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RevocationReason {
+    Unspecified,
+    /// The issuer's or the subject's key is compromised.
+    KeyCompromise,
+    /// The claim is replaced by another one.
+    Superseded,
+    /// The claim data turned out to be wrong.
+    Misissued,
+    /// The issuer no longer vouches for the subject.
+    CessationOfOperation,
+}
+
+/// Revocation records why and when a claim was revoked.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Revocation {
+    /// Reason of the revocation.
+    pub reason: RevocationReason,
+    /// Block timestamp of the revocation.
+    pub timestamp: u64,
+}
+
+/// Domain tag of the [`WithdrawalSigningPayload`].
+pub const WITHDRAWAL_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/verification-withdrawal/v1";
+
+/// WithdrawalSigningPayload is what a verifier signs to withdraw its verification of a claim.
+/// Off-chain signers MUST sign [`WithdrawalSigningPayload::signing_bytes`]
+/// of the payload built with [`WithdrawalSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct WithdrawalSigningPayload {
+    /// Domain tag. Always equals to [`WITHDRAWAL_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Verifier's public key.
+    pub verifier: PublicKey,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Claim's id.
+    pub piece_id: PieceId,
+    /// Reason of the withdrawal.
+    pub reason: WithdrawalReason,
+    /// Verifier's current nonce.
+    pub nonce: u64,
+}
+
+impl WithdrawalSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        verifier: PublicKey,
+        subject: PublicKey,
+        piece_id: PieceId,
+        reason: WithdrawalReason,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: WITHDRAWAL_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            verifier,
+            subject,
+            piece_id,
+            reason,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Reason code of a verification withdrawal.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WithdrawalReason {
+    Unspecified,
+    /// The verifier learned that the claim is false.
+    ClaimFalse,
+    /// The verifier's key is compromised.
+    KeyCompromise,
+    /// The verifier no longer vouches for the claim.
+    CessationOfOperation,
+}
+
+/// Withdrawal records why and when a verifier withdrew its verification.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Withdrawal {
+    /// Reason of the withdrawal.
+    pub reason: WithdrawalReason,
+    /// Block timestamp of the withdrawal.
+    pub timestamp: u64,
+}
+
+/// VerificationPolicy is the quorum of verifiers a claim needs to be sufficiently verified.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct VerificationPolicy {
+    /// Minimum number of the verifications that aren't withdrawn.
+    pub threshold: u32,
+    /// Verifiers whose verifications count. Empty if every verifier counts.
+    pub verifiers: Vec<PublicKey>,
+}
+
+/// Domain tag of the [`VerificationPolicySigningPayload`].
+pub const VERIFICATION_POLICY_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/verification-policy/v1";
+
+/// VerificationPolicySigningPayload is what a claim's subject or issuer signs to set its verification policy.
+/// Off-chain signers MUST sign [`VerificationPolicySigningPayload::signing_bytes`]
+/// of the payload built with [`VerificationPolicySigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct VerificationPolicySigningPayload {
+    /// Domain tag. Always equals to [`VERIFICATION_POLICY_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Subject's or issuer's public key.
+    pub owner: PublicKey,
+    /// Subject's public key.
+    pub subject: PublicKey,
+    /// Claim's id.
+    pub piece_id: PieceId,
+    /// Verification policy of the claim.
+    pub policy: VerificationPolicy,
+    /// Owner's current nonce.
+    pub nonce: u64,
+}
+
+impl VerificationPolicySigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        owner: PublicKey,
+        subject: PublicKey,
+        piece_id: PieceId,
+        policy: VerificationPolicy,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: VERIFICATION_POLICY_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            owner,
+            subject,
+            piece_id,
+            policy,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// Domain tag of the [`IssuerRegistrationSigningPayload`].
+pub const ISSUER_REGISTRATION_SIGNING_DOMAIN: &[u8] = b"gear-dapps/identity/issuer-registration/v1";
+
+/// IssuerRegistrationSigningPayload is what an issuer signs to register itself in the issuer registry.
+/// Off-chain signers MUST sign [`IssuerRegistrationSigningPayload::signing_bytes`]
+/// of the payload built with [`IssuerRegistrationSigningPayload::new`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq)]
+pub struct IssuerRegistrationSigningPayload {
+    /// Domain tag. Always equals to [`ISSUER_REGISTRATION_SIGNING_DOMAIN`].
+    pub domain: Vec<u8>,
+    /// Identity program id.
+    pub program_id: ActorId,
+    /// Issuer's public key.
+    pub issuer: PublicKey,
+    /// Issuer's metadata.
+    pub metadata: IssuerMetadata,
+    /// Issuer's current nonce.
+    pub nonce: u64,
+}
+
+impl IssuerRegistrationSigningPayload {
+    pub fn new(
+        program_id: ActorId,
+        issuer: PublicKey,
+        metadata: IssuerMetadata,
+        nonce: u64,
+    ) -> Self {
+        Self {
+            domain: ISSUER_REGISTRATION_SIGNING_DOMAIN.to_vec(),
+            program_id,
+            issuer,
+            metadata,
+            nonce,
+        }
+    }
+
+    /// Returns the SCALE-encoded payload to be signed.
+    pub fn signing_bytes(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+
+/// IssuerMetadata describes a registered issuer.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct IssuerMetadata {
+    /// Issuer's name.
+    pub name: String,
+    /// Issuer's website URL.
+    pub url: String,
+    /// Hash of the issuer's policy document.
+    pub policy_hash: [u8; 32],
+    /// Schemas of the claims the issuer issues.
+    pub claim_types: Vec<SchemaId>,
+}
+
+/// IssuerInfo is an entry of the issuer registry.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct IssuerInfo {
+    /// Issuer's metadata.
+    pub metadata: IssuerMetadata,
+    /// Whether the admin marked the issuer as trusted.
+    pub trusted: bool,
+}
+
+/// AttributePresentation reveals the value of a claim's attribute
+/// committed to with [`HashAlgorithm::commitment`].
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct AttributePresentation {
+    /// Attribute name from the claim's schema.
+    pub attribute: String,
+    /// Revealed attribute value.
+    pub value: Vec<u8>,
+    /// Salt of the attribute commitment.
+    pub salt: [u8; 32],
+}
+
+/// Maximum [`ThresholdProof::threshold`], which bounds the hash rounds of a threshold check.
+pub const MAX_THRESHOLD: u32 = 10_000;
+
+/// ThresholdProof proves that a numeric attribute committed to with
+/// [`HashAlgorithm::hash_chain`] is at least the `threshold`.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct ThresholdProof {
+    /// Attribute name from the claim's schema.
+    pub attribute: String,
+    /// Proven lower bound of the attribute value. MUST NOT exceed [`MAX_THRESHOLD`].
+    pub threshold: u32,
+    /// `H^(n - threshold)(seed)` where `n` is the attribute value.
+    pub hash: [u8; 32],
+}
+
+/// SubscriptionTarget is what a program subscribes to
+/// to be notified about the claims' changes.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SubscriptionTarget {
+    /// All the claims of the subject.
+    Subject(PublicKey),
+    /// All the claims of the issuer.
+    Issuer(PublicKey),
+    /// A specific claim of the subject.
+    Claim(PublicKey, PieceId),
+}
+
+/// Maximum number of the targets a subscriber can subscribe to.
+pub const MAX_SUBSCRIPTIONS: usize = 16;
+/// Maximum number of the subscribers of a target.
+pub const MAX_TARGET_SUBSCRIBERS: usize = 32;
+/// Maximum number of the notifications a subscriber can reserve gas for.
+pub const MAX_RESERVED_NOTIFICATIONS: u32 = 64;
+/// Gas reserved for each notification.
+pub const NOTIFICATION_GAS: u64 = 1_000_000_000;
+/// Number of blocks the notification gas is reserved for.
+pub const NOTIFICATION_RESERVATION_BLOCKS: u32 = 100_000;
+
+/// Authority performing an action on a claim.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Authority {
+    /// Holder of a public key, e.g. the claim's issuer or subject.
+    Key {
+        /// Public key.
+        key: PublicKey,
+        /// Proof of the `key` control.
+        proof: KeyProof,
+    },
+    /// The program admin, i.e. the actor that initialized the program.
+    Admin,
+}
+
+/// KeyProof proves that the message sender controls a public key.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyProof {
+    /// Key's signature of the action signing payload.
+    Signature {
+        /// Signature of the payload.
+        signature: Signature,
+        /// Key's current nonce. Stale or reused nonces are rejected.
+        nonce: u64,
+    },
+    /// The message sender is linked to the key with [`IdentityAction::LinkKey`].
+    LinkedActor,
 }
 
 /// Claim is a main object stored inside the identity storage.
@@ -26,16 +874,54 @@ pub struct Claim {
     /// Issuer's  public key (e.g. who issued the claim). Can be equal to subject keys
     /// if the subject issues any claim about himself.
     pub issuer: PublicKey,
-    /// Issuer's signature with the issuer keypair.
+    /// Issuer's signature of the [`ClaimSigningPayload`].
     pub issuer_signature: Signature,
     /// Subject's public key.
     pub subject: PublicKey,
-    /// Map of verifiers PublicKey -> Signature
-    pub verifiers: Vec<(PublicKey, Signature)>,
+    /// Verifications of the claim. A verifier can verify the claim only once.
+    pub verifiers: Vec<Verification>,
+    /// Issuer's asserted date and the block the claim was issued in.
+    /// Set by the program on issuance.
+    pub issuance: IssuanceDate,
     /// Internal data of the claim
     pub data: ClaimData,
 }
 
+impl Claim {
+    /// Returns the hash signed by the claim verifiers (see [`VerificationSigningPayload`]).
+    ///
+    /// It's the Sha256 of the SCALE-encoded issuer, subject and claim data
+    /// except for the data status, which changes during the claim lifecycle.
+    pub fn hash(&self) -> [u8; 32] {
+        let data = &self.data;
+        Sha256::digest(
+            (
+                self.issuer,
+                self.subject,
+                &data.hashed_info,
+                data.schema_id,
+                data.issuance_date,
+                data.valid_from,
+                data.valid_until,
+                &data.accredited_types,
+            )
+                .encode(),
+        )
+        .into()
+    }
+}
+
+/// Verification is a verifier's attestation of a claim.
+#[derive(Decode, Encode, TypeInfo, Debug, Clone, PartialEq, Eq)]
+pub struct Verification {
+    /// Verifier's public key.
+    pub verifier: PublicKey,
+    /// Verifier's signature of the [`VerificationSigningPayload`].
+    pub signature: Signature,
+    /// Verifier's statement about the claim.
+    pub statement: String,
+}
+
 #[derive(Debug, Decode, Encode, TypeInfo)]
 pub enum IdentityAction {
     /// Issues a new claim either by a subject himself
@@ -43,45 +929,267 @@ pub enum IdentityAction {
     ///
     /// # Requirements:
     /// * all public keys and signatures MUST be non-zero arrays
+    /// * a claim with `valid_until` MUST be issued with at least
+    ///   [`EXPIRY_GAS`] and [`EXPIRY_HANDLING_GAS`] of gas available
     IssueClaim {
         /// Issuer's public key.
         issuer: PublicKey,
-        /// Issuer's signature with his keypair.
+        /// Issuer's signature of the [`ClaimSigningPayload`]. MUST be of the `issuer` key scheme.
+        /// Claims with a signature not matching the `issuer` key are rejected.
         issuer_signature: Signature,
         /// Subject's public key.
         subject: PublicKey,
         /// Claim's data.
         data: ClaimData,
+        /// Issuer's current nonce. Stale or reused nonces are rejected.
+        nonce: u64,
     },
-    /// Changes a validation status of the claim.
-    /// Can only be performed by a subject or an issuer of the claim.
+    /// Changes a status of the claim.
+    /// Can only be performed by a subject or an issuer of the claim or by the admin
+    /// according to the [`ClaimStatus`] transitions.
     ///
     /// # Requirements:
     /// * all public keys and signatures MUST be non-zero arrays
     ChangeClaimValidationStatus {
-        /// Validator's public key. Can be either a subject's or an issuer's one.
-        validator: PublicKey,
+        /// Validator. Can be either a subject's or an issuer's key or the admin.
+        /// A key signature MUST be made of the [`StatusChangeSigningPayload`].
+        validator: Authority,
         /// Subject's public key.
         subject: PublicKey,
         /// Claim's id.
         piece_id: PieceId,
         /// New status of the claim.
-        status: bool,
+        status: ClaimStatus,
     },
     /// Verify a specific claim with a public key and a signature.
-    /// Can not be performed by an issuer or a subject.
+    /// Can not be performed by an issuer or a subject. A verifier can verify the claim only once.
     ///
     /// # Requirements:
     /// * all public keys and signatures MUST be non-zero arrays
     VerifyClaim {
         /// Verifier's public key.
         verifier: PublicKey,
-        /// Verifier's signature.
+        /// Verifier's signature of the [`VerificationSigningPayload`].
         verifier_signature: Signature,
         /// Subject's public key.
         subject: PublicKey,
         /// Claim's id.
         piece_id: PieceId,
+        /// Verifier's statement about the claim.
+        statement: String,
+        /// Verifier's current nonce. Stale or reused nonces are rejected.
+        nonce: u64,
+    },
+    /// Permanently revokes the claim.
+    /// Can only be performed by an issuer of the claim.
+    /// A revoked claim is invalid and its validation status can't be changed anymore.
+    ///
+    /// # Requirements:
+    /// * all public keys and signatures MUST be non-zero arrays
+    RevokeClaim {
+        /// Issuer's public key.
+        issuer: PublicKey,
+        /// Proof of the `issuer` key control.
+        /// A signature MUST be made of the [`RevocationSigningPayload`].
+        proof: KeyProof,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Reason of the revocation.
+        reason: RevocationReason,
+    },
+    /// Withdraws the verification of the claim.
+    /// Can only be performed by a verifier of the claim.
+    /// The verification stays in the claim's `verifiers` along with the withdrawal record,
+    /// and the verifier can't verify the claim again.
+    ///
+    /// # Requirements:
+    /// * all public keys and signatures MUST be non-zero arrays
+    WithdrawVerification {
+        /// Verifier's public key.
+        verifier: PublicKey,
+        /// Proof of the `verifier` key control.
+        /// A signature MUST be made of the [`WithdrawalSigningPayload`].
+        proof: KeyProof,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Reason of the withdrawal.
+        reason: WithdrawalReason,
+    },
+    /// Sets the verification policy of the claim replacing the previous one.
+    /// Can only be performed by a subject or an issuer of the claim.
+    /// [`IdentityEvent::QuorumReached`] is sent to the zero address
+    /// the first time the claim satisfies the policy.
+    ///
+    /// # Requirements:
+    /// * all public keys and signatures MUST be non-zero arrays
+    SetVerificationPolicy {
+        /// Subject's or issuer's public key.
+        owner: PublicKey,
+        /// Proof of the `owner` key control.
+        /// A signature MUST be made of the [`VerificationPolicySigningPayload`].
+        proof: KeyProof,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Verification policy. The threshold MUST be positive and,
+        /// if the verifiers are listed, MUST NOT exceed their number.
+        policy: VerificationPolicy,
+    },
+    /// Checks the attribute of a claim in the [`HashedInfo::MerkleRoot`] mode
+    /// and replies with [`IdentityEvent::MerkleAttributeChecked`].
+    /// Intended for programs that can't read the state.
+    CheckMerkleAttribute {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Attribute name from the claim's schema.
+        attribute: String,
+        /// Hash of the attribute.
+        leaf: [u8; 32],
+        /// Merkle path of the attribute (see [`merkle_path`]).
+        path: Vec<[u8; 32]>,
+    },
+    /// Registers a claim schema.
+    RegisterSchema {
+        /// Schema's name.
+        name: String,
+        /// Ordered attribute names. MUST be unique.
+        attributes: Vec<String>,
+        /// Algorithm the attributes are hashed with.
+        hash_algorithm: HashAlgorithm,
+    },
+    /// Registers a relying-party policy owned by the message sender.
+    RegisterPolicy {
+        /// Policy's name.
+        name: String,
+        /// Required schema of the claims. MUST be registered.
+        schema_id: SchemaId,
+        /// Accepted issuers. Empty if every issuer is accepted.
+        issuers: Vec<PublicKey>,
+        /// Minimum number of the verifications that aren't withdrawn.
+        min_verifiers: u32,
+        /// Maximum time in milliseconds since the claim issuance block.
+        max_age: Option<u64>,
+    },
+    /// Gets the claim's status at the current block timestamp (see [`ClaimData::status_at`])
+    /// and replies with [`IdentityEvent::ClaimStatusQueried`].
+    /// Intended for programs that can't read the state (e.g. with `msg::send_for_reply`).
+    QueryClaimStatus {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+    },
+    /// Checks the claim with a hash from its data set like [`IdentityStateQuery::CheckClaim`]
+    /// and replies with [`IdentityEvent::ClaimChecked`].
+    /// Intended for programs that can't read the state (e.g. with `msg::send_for_reply`).
+    QueryCheckClaim {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Hash being checked.
+        hash: [u8; 32],
+        /// Whether the claim's issuer MUST be trusted.
+        trusted_only: bool,
+    },
+    /// Finds the claims of the subject satisfying the relying-party policy
+    /// and replies with [`IdentityEvent::PolicySatisfied`].
+    /// Intended for programs that can't read the state (e.g. with `msg::send_for_reply`).
+    QuerySatisfiesPolicy {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Policy's id.
+        policy_id: PolicyId,
+    },
+    /// Registers an issuer or updates its metadata in the issuer registry.
+    /// An updated issuer becomes untrusted until the admin marks it as trusted again.
+    ///
+    /// # Requirements:
+    /// * all public keys and signatures MUST be non-zero arrays
+    RegisterIssuer {
+        /// Issuer's public key.
+        issuer: PublicKey,
+        /// Proof of the `issuer` key control.
+        /// A signature MUST be made of the [`IssuerRegistrationSigningPayload`].
+        proof: KeyProof,
+        /// Issuer's metadata.
+        metadata: IssuerMetadata,
+    },
+    /// Marks a registered issuer as trusted or untrusted.
+    /// Can only be performed by the admin.
+    SetIssuerTrust {
+        /// Issuer's public key.
+        issuer: PublicKey,
+        /// Whether the issuer is trusted.
+        trusted: bool,
+    },
+    /// Links a public key to the message sender,
+    /// so the sender can act on behalf of the key with [`KeyProof::LinkedActor`].
+    /// A key can be linked to several actors and vice versa.
+    LinkKey {
+        /// Public key being linked.
+        key: PublicKey,
+        /// Key's signature of the [`KeyLinkSigningPayload`] with the sender as the actor.
+        signature: Signature,
+        /// Key's current nonce. Stale or reused nonces are rejected.
+        nonce: u64,
+    },
+    /// Unlinks a public key from the message sender.
+    UnlinkKey {
+        /// Public key being unlinked.
+        key: PublicKey,
+    },
+    /// Unlinks a public key from an actor on behalf of the key holder,
+    /// e.g. when the actor is compromised. Can be sent by anyone.
+    UnlinkActor {
+        /// Public key being unlinked.
+        key: PublicKey,
+        /// Actor the key is unlinked from.
+        actor: ActorId,
+        /// Key's signature of the [`KeyUnlinkSigningPayload`].
+        signature: Signature,
+        /// Key's current nonce. Stale or reused nonces are rejected.
+        nonce: u64,
+    },
+    /// Switches the claim to `Expired` once its `valid_until` has passed
+    /// and sends [`IdentityEvent::ClaimExpired`] to the zero address and the subscribers.
+    /// Sent by the program to itself with a delay when a claim with `valid_until` is issued,
+    /// but can be performed by anyone. The program reschedules it with the rest of its gas
+    /// if it arrives too early.
+    ExpireClaim {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+    },
+    /// Subscribes the message sender to the claims' changes
+    /// and reserves [`NOTIFICATION_GAS`] for each of the `notifications`.
+    /// Subscribing to the same target again only reserves more notifications.
+    ///
+    /// Each subscriber is sent the [`IdentityEvent`] of a claim issuance, status change,
+    /// verification, withdrawal or revocation matching any of its targets while its reserved notifications last.
+    /// At most [`MAX_SUBSCRIPTIONS`] targets and [`MAX_RESERVED_NOTIFICATIONS`] notifications are allowed
+    /// and a new subscription MUST reserve at least one notification.
+    /// A target has at most [`MAX_TARGET_SUBSCRIBERS`] subscribers.
+    /// A subscriber whose notifications run out is unsubscribed from all its targets.
+    Subscribe {
+        /// Subscription target.
+        target: SubscriptionTarget,
+        /// Number of the notifications to reserve gas for.
+        notifications: u32,
+    },
+    /// Unsubscribes the message sender from the target.
+    /// The reserved gas is returned once the sender has no targets left.
+    Unsubscribe {
+        /// Subscription target.
+        target: SubscriptionTarget,
     },
 }
 
@@ -96,14 +1204,16 @@ pub enum IdentityEvent {
         piece_id: PieceId,
     },
     ClaimValidationChanged {
-        /// Validator's public key.
-        validator: PublicKey,
+        /// Validator's public key. `None` if the status is changed by the admin.
+        validator: Option<PublicKey>,
         /// Subjects's public key.
         subject: PublicKey,
         /// Claims' id.
         piece_id: PieceId,
-        /// Claim's new validation status.
-        status: bool,
+        /// Claim's previous status.
+        old_status: ClaimStatus,
+        /// Claim's new status.
+        new_status: ClaimStatus,
     },
     VerifiedClaim {
         /// Verifier's public key.
@@ -113,6 +1223,205 @@ pub enum IdentityEvent {
         /// Claim's id.
         piece_id: PieceId,
     },
+    ClaimRevoked {
+        /// Issuer's public key.
+        issuer: PublicKey,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Claim's status before the revocation.
+        old_status: ClaimStatus,
+        /// Reason of the revocation.
+        reason: RevocationReason,
+        /// Block timestamp of the revocation.
+        timestamp: u64,
+    },
+    VerificationWithdrawn {
+        /// Verifier's public key.
+        verifier: PublicKey,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Reason of the withdrawal.
+        reason: WithdrawalReason,
+        /// Block timestamp of the withdrawal.
+        timestamp: u64,
+    },
+    VerificationPolicySet {
+        /// Subject's or issuer's public key.
+        owner: PublicKey,
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// New verification policy.
+        policy: VerificationPolicy,
+    },
+    /// Sent to the zero address the first time the claim satisfies its verification policy.
+    QuorumReached {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+    },
+    MerkleAttributeChecked {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Attribute name.
+        attribute: String,
+        /// Whether the attribute is included in the `Active` claim.
+        included: bool,
+    },
+    SchemaRegistered {
+        /// Schema's id generated automatically.
+        schema_id: SchemaId,
+    },
+    PolicyRegistered {
+        /// Policy's id generated automatically.
+        policy_id: PolicyId,
+    },
+    ClaimStatusQueried {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Claim's status at the current block timestamp or `None` if there's no such claim.
+        status: Option<ClaimStatus>,
+    },
+    ClaimChecked {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Checked hash.
+        hash: [u8; 32],
+        /// Whether the hash is in the `Active` claim of a trusted issuer if required.
+        valid: bool,
+    },
+    PolicySatisfied {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Policy's id.
+        policy_id: PolicyId,
+        /// Claims satisfying the policy in the ascending order. Empty if the policy isn't satisfied.
+        piece_ids: Vec<PieceId>,
+    },
+    IssuerRegistered {
+        /// Issuer's public key.
+        issuer: PublicKey,
+    },
+    IssuerTrustChanged {
+        /// Issuer's public key.
+        issuer: PublicKey,
+        /// Whether the issuer is trusted.
+        trusted: bool,
+    },
+    KeyLinked {
+        /// Linked public key.
+        key: PublicKey,
+        /// Actor the key is linked to.
+        actor: ActorId,
+    },
+    KeyUnlinked {
+        /// Unlinked public key.
+        key: PublicKey,
+        /// Actor the key was linked to.
+        actor: ActorId,
+    },
+    ClaimExpired {
+        /// Subject's public key.
+        subject: PublicKey,
+        /// Claim's id.
+        piece_id: PieceId,
+        /// Claim's status before the expiry.
+        old_status: ClaimStatus,
+    },
+    Subscribed {
+        /// Subscribed actor.
+        subscriber: ActorId,
+        /// Subscription target.
+        target: SubscriptionTarget,
+        /// Number of the subscriber's notifications with the reserved gas.
+        reserved_notifications: u32,
+    },
+    Unsubscribed {
+        /// Unsubscribed actor.
+        subscriber: ActorId,
+        /// Subscription target.
+        target: SubscriptionTarget,
+    },
+}
+
+/// IdentityError is replied instead of an [`IdentityEvent`] when an action fails.
+#[derive(Debug, Decode, Encode, TypeInfo, Clone, Copy, PartialEq, Eq)]
+pub enum IdentityError {
+    /// The nonce isn't the current one of the key.
+    InvalidNonce,
+    /// The signature isn't the key's signature of the expected payload.
+    InvalidSignature,
+    /// The message sender isn't linked to the key.
+    SenderNotLinked,
+    /// The key is already linked to the message sender.
+    KeyAlreadyLinked,
+    /// The key isn't linked to the actor.
+    KeyNotLinked,
+    /// The subject has no claim with the provided id.
+    ClaimNotFound,
+    /// Only the claim's subject or issuer can change it.
+    NotSubjectOrIssuer,
+    /// The message sender isn't the admin.
+    NotAdmin,
+    /// The claim can't move from its current status to the requested one.
+    InvalidTransition,
+    /// The validator's role doesn't allow the requested status transition.
+    TransitionNotPermitted,
+    /// A claim MUST be issued either `Pending` or `Active`.
+    InvalidInitialStatus,
+    /// `valid_until` MUST be in the future and after `valid_from`.
+    InvalidValidityPeriod,
+    /// `issuance_date` is more than [`MAX_ISSUANCE_DATE_DRIFT`] ahead of the block timestamp.
+    IssuanceDateInFuture,
+    /// The issuer isn't registered in the issuer registry.
+    IssuerNotRegistered,
+    /// Schema's attribute names MUST be unique.
+    InvalidSchema,
+    /// There's no schema with the provided id.
+    SchemaNotFound,
+    /// There's no relying-party policy with the provided id.
+    PolicyNotFound,
+    /// The number of the claim's hashes differs from the number of the schema's attributes.
+    SchemaMismatch,
+    /// The claim's subject or issuer can't verify it.
+    VerifierIsSubjectOrIssuer,
+    /// Only the claim's issuer can revoke it.
+    NotIssuer,
+    /// The claim is revoked and can't be changed anymore.
+    ClaimRevoked,
+    /// The key hasn't verified the claim.
+    NotVerifier,
+    /// The verifier has withdrawn its verification of the claim.
+    VerificationWithdrawn,
+    /// The verifier has already verified the claim.
+    AlreadyVerified,
+    /// The policy threshold is zero or exceeds the number of the listed verifiers.
+    InvalidPolicy,
+    /// The subscriber would exceed [`MAX_SUBSCRIPTIONS`] or [`MAX_RESERVED_NOTIFICATIONS`],
+    /// or the target would exceed [`MAX_TARGET_SUBSCRIBERS`].
+    SubscriptionLimitExceeded,
+    /// The message sender isn't subscribed to the target.
+    NotSubscribed,
+    /// A new subscription reserves no notifications.
+    NoNotificationsReserved,
+    /// There's not enough gas to reserve for the notifications.
+    GasReservationFailed,
+    /// The claim has no `valid_until` or it hasn't passed yet.
+    ClaimNotExpired,
+    /// There's not enough gas to schedule the claim expiry.
+    InsufficientGas,
 }
 
 #[derive(Debug, Decode, Encode, TypeInfo)]
@@ -121,26 +1430,58 @@ pub enum IdentityStateQuery {
     ///
     /// Arguments:
     /// `PublicKey` - is the public key of a user whose claims are queried
-    UserClaims(PublicKey),
+    /// `bool` - is whether to return only the claims of trusted issuers
+    UserClaims(PublicKey, bool),
     /// Get a specific claim with the provided public key and a claim id.
     ///
     /// Arguments:
     /// `PublicKey` - is the public key of a user whose claim is queried
     /// `PieceId` - is the claim id
     Claim(PublicKey, PieceId),
-    /// Get all the verifiers' public keys for a corresponding claim.
+    /// Get the public keys of the verifiers that haven't withdrawn their verification of a claim.
     ///
     /// Arguments:
     /// `PublicKey` - is the public key of a user whose claim is queried
     /// `PieceId` - is the claim id
     Verifiers(PublicKey, PieceId),
-    /// Get claim's validation status.
+    /// Get all the verifiers' public keys for a corresponding claim
+    /// including the verifiers that withdrew their verification.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    AllVerifiers(PublicKey, PieceId),
+    /// Get the verification policy of a claim.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    VerificationPolicy(PublicKey, PieceId),
+    /// Check whether the claim satisfies its verification policy.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// If the claim is `Active` at the current block timestamp, has a policy and at least `threshold`
+    /// of the policy's verifiers verified the claim without withdrawing then true is returned.
+    /// Otherwise - false.
+    IsSufficientlyVerified(PublicKey, PieceId),
+    /// Get the withdrawal of a claim verification.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `PublicKey` - is the public key of the verifier
+    /// The withdrawal reason and timestamp are returned if the verification is withdrawn.
+    Withdrawal(PublicKey, PieceId, PublicKey),
+    /// Get claim's status at the current block timestamp (see [`ClaimData::status_at`]).
     ///
     /// Arguments:
     /// `PublicKey` - is the public key of a user whose claim is queried
     /// `PieceId` - is the claim id
     ValidationStatus(PublicKey, PieceId),
-    /// Get claim's issuance date.
+    /// Get claim's issuance date asserted by the issuer
+    /// along with the block timestamp and height of the issuance.
     ///
     /// Arguments:
     /// `PublicKey` - is the public key of a user whose claim is queried
@@ -152,8 +1493,131 @@ pub enum IdentityStateQuery {
     /// `PublicKey` - is the public key of a user whose claim is queried
     /// `PieceId` - is the claim id
     /// `[u8; 32]` - is the hash being queried.
-    /// If it is in the claim hashed_info set then true is returned. Otherwise - false.
-    CheckClaim(PublicKey, PieceId, [u8; 32]),
+    /// `bool` - is whether the claim's issuer MUST be trusted
+    /// If it is in the claim hashed_info set and the claim is `Active`
+    /// at the current block timestamp then true is returned. Otherwise - false.
+    CheckClaim(PublicKey, PieceId, [u8; 32], bool),
+    /// Check the claim's attribute with a hash.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `String` - is the attribute name from the claim's schema
+    /// `[u8; 32]` - is the hash being queried.
+    /// If it is the hash of the attribute in the claim hashed_info and the claim is `Active`
+    /// at the current block timestamp then true is returned. Otherwise - false.
+    CheckClaimAttribute(PublicKey, PieceId, String, [u8; 32]),
+    /// Check the attribute value and salt revealed by the subject.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `AttributePresentation` - is the revealed attribute.
+    /// If the attribute's commitment made with the schema's hash algorithm
+    /// is the hash of the attribute in the claim hashed_info and the claim is `Active`
+    /// at the current block timestamp then true is returned. Otherwise - false.
+    CheckPresentation(PublicKey, PieceId, AttributePresentation),
+    /// Check the threshold proof revealed by the subject (e.g. "over 18").
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `ThresholdProof` - is the proof.
+    /// If the `threshold` doesn't exceed [`MAX_THRESHOLD`],
+    /// the proof hash hashed `threshold` times with the schema's hash algorithm
+    /// is the hash of the attribute in the claim hashed_info and the claim is `Active`
+    /// at the current block timestamp then true is returned. Otherwise - false.
+    CheckThreshold(PublicKey, PieceId, ThresholdProof),
+    /// Check the attribute of a claim in the [`HashedInfo::MerkleRoot`] mode.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `String` - is the attribute name from the claim's schema
+    /// `[u8; 32]` - is the hash of the attribute
+    /// `Vec<[u8; 32]>` - is the Merkle path of the attribute (see [`merkle_path`]).
+    /// If the attribute hash is included at the attribute's position and the claim is `Active`
+    /// at the current block timestamp then true is returned. Otherwise - false.
+    CheckMerkleAttribute(PublicKey, PieceId, String, [u8; 32], Vec<[u8; 32]>),
+    /// Get the current nonce of a public key.
+    /// Signed payloads MUST carry it to be accepted.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key whose nonce is queried
+    Nonce(PublicKey),
+    /// Get all the actors linked to a public key.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key whose actors are queried
+    KeyActors(PublicKey),
+    /// Get all the public keys linked to an actor.
+    ///
+    /// Arguments:
+    /// `ActorId` - is the actor whose public keys are queried
+    ActorKeys(ActorId),
+    /// Get the subscription targets of an actor and the number of its notifications with the reserved gas.
+    ///
+    /// Arguments:
+    /// `ActorId` - is the subscriber
+    Subscription(ActorId),
+    /// Check whether the claim is revoked.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// The revocation reason and timestamp are returned if the claim is revoked.
+    IsRevoked(PublicKey, PieceId),
+    /// Get the claims that are expired or expire soon.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claims are queried
+    /// `u64` - is the period in milliseconds from the current block timestamp.
+    /// Not revoked claims with `valid_until` before its end are returned
+    /// with their `valid_until` in the ascending order.
+    ExpiringClaims(PublicKey, u64),
+    /// Get the issuer registry entry.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of the issuer
+    Issuer(PublicKey),
+    /// Resolve whether the claim's issuer chains back to a root authority.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claim is queried
+    /// `PieceId` - is the claim id
+    /// `PublicKey` - is the public key of the root authority
+    /// `u32` - is the maximum number of accreditations in the chain
+    /// The shortest chain of issuers from the claim's issuer to the root is returned.
+    /// Each issuer in the chain holds an `Active` accreditation of the claim's type
+    /// issued by the next one. The claim's issuer is the root itself at zero depth.
+    AccreditationChain(PublicKey, PieceId, PublicKey, u32),
+    /// Get a schema.
+    ///
+    /// Arguments:
+    /// `SchemaId` - is the schema id
+    Schema(SchemaId),
+    /// Get all the schemas.
+    Schemas,
+    /// Get all the claims of a schema.
+    ///
+    /// Arguments:
+    /// `SchemaId` - is the schema id
+    ClaimsBySchema(SchemaId),
+    /// Get a relying-party policy.
+    ///
+    /// Arguments:
+    /// `PolicyId` - is the policy id
+    Policy(PolicyId),
+    /// Get the claims of a subject satisfying a relying-party policy.
+    ///
+    /// Arguments:
+    /// `PublicKey` - is the public key of a user whose claims are queried
+    /// `PolicyId` - is the policy id
+    /// A claim satisfies the policy if it is `Active` at the current block timestamp
+    /// (e.g. not revoked), is of the policy's schema, is issued by an accepted issuer
+    /// not earlier than `max_age` ago and has at least `min_verifiers` verifications
+    /// that aren't withdrawn. The claim ids are returned in the ascending order.
+    SatisfiesPolicy(PublicKey, PolicyId),
 }
 
 #[derive(Debug, Decode, Encode, TypeInfo)]
@@ -161,11 +1625,32 @@ pub enum IdentityStateReply {
     UserClaims(Vec<(PieceId, Claim)>),
     Claim(Option<Claim>),
     Verifiers(Vec<PublicKey>),
-    ValidationStatus(bool),
-    Date(u64),
+    AllVerifiers(Vec<PublicKey>),
+    Withdrawal(Option<Withdrawal>),
+    VerificationPolicy(Option<VerificationPolicy>),
+    IsSufficientlyVerified(bool),
+    ValidationStatus(Option<ClaimStatus>),
+    Date(Option<IssuanceDate>),
     CheckedClaim(PublicKey, PieceId, bool),
+    CheckedClaimAttribute(PublicKey, PieceId, String, bool),
+    CheckedPresentation(PublicKey, PieceId, bool),
+    CheckedThreshold(PublicKey, PieceId, bool),
+    CheckedMerkleAttribute(PublicKey, PieceId, String, bool),
+    Nonce(u64),
+    KeyActors(Vec<ActorId>),
+    ActorKeys(Vec<PublicKey>),
+    Subscription(Vec<SubscriptionTarget>, u32),
+    IsRevoked(Option<Revocation>),
+    ExpiringClaims(Vec<(PieceId, u64)>),
+    Issuer(Option<IssuerInfo>),
+    AccreditationChain(Option<Vec<PublicKey>>),
+    Schema(Option<Schema>),
+    Schemas(Vec<Schema>),
+    ClaimsBySchema(Vec<(PieceId, Claim)>),
+    Policy(Option<RelyingPartyPolicy>),
+    SatisfiesPolicy(Vec<PieceId>),
 }
 
-/// Initializes an identity storage.
+/// Initializes an identity storage. The message sender becomes the admin.
 #[derive(Decode, Encode, TypeInfo)]
 pub struct InitIdentity;
@@ -2,102 +2,1130 @@
 
 pub mod io;
 
+mod crypto;
+
 use crate::io::*;
-use gstd::{msg, prelude::*};
-use hashbrown::HashMap;
+use gstd::{exec, msg, prelude::*, ActorId, ReservationId};
+use hashbrown::{HashMap, HashSet};
 
 #[derive(Debug, Default)]
 pub struct IdentityStorage {
     user_claims: HashMap<PublicKey, HashMap<PieceId, Claim>>,
     piece_counter: u128,
+    nonces: HashMap<PublicKey, u64>,
+    key_actors: HashMap<PublicKey, Vec<ActorId>>,
+    actor_keys: HashMap<ActorId, Vec<PublicKey>>,
+    revocations: HashMap<(PublicKey, PieceId), Revocation>,
+    withdrawals: HashMap<(PublicKey, PieceId, PublicKey), Withdrawal>,
+    verification_policies: HashMap<(PublicKey, PieceId), VerificationPolicy>,
+    quorums: HashSet<(PublicKey, PieceId)>,
+    issuers: HashMap<PublicKey, IssuerInfo>,
+    schemas: HashMap<SchemaId, Schema>,
+    schema_counter: SchemaId,
+    policies: HashMap<PolicyId, RelyingPartyPolicy>,
+    policy_counter: PolicyId,
+    subscribers: HashMap<ActorId, Subscriber>,
+    target_subscribers: HashMap<SubscriptionTarget, Vec<ActorId>>,
+    admin: ActorId,
+}
+
+/// Subscription targets of an actor and the gas reserved for its notifications.
+#[derive(Debug, Default)]
+struct Subscriber {
+    targets: Vec<SubscriptionTarget>,
+    reservations: Vec<ReservationId>,
+}
+
+/// Role of a validator towards a claim.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Role {
+    Issuer,
+    Subject,
+    Admin,
+}
+
+/// Returns the roles allowed to move a claim from the `from` status to the `to` one
+/// or `None` if there is no such transition. Revocation is performed separately.
+fn transition_roles(from: ClaimStatus, to: ClaimStatus) -> Option<&'static [Role]> {
+    use ClaimStatus::*;
+
+    match (from, to) {
+        (Pending, Active) | (Suspended, Active) => Some(&[Role::Issuer, Role::Admin]),
+        (Active, Suspended) => Some(&[Role::Issuer, Role::Subject, Role::Admin]),
+        (Pending | Active | Suspended, Expired) => Some(&[Role::Admin]),
+        _ => None,
+    }
 }
 
 static mut IDENTITY: Option<IdentityStorage> = None;
 
+/// Sends [`IdentityAction::ExpireClaim`] with the `gas_limit` to the program itself
+/// with a delay of the expected number of blocks of the `block_duration` until `valid_until`.
+fn schedule_expiry(
+    subject: PublicKey,
+    piece_id: PieceId,
+    valid_until: u64,
+    block_duration: u64,
+    gas_limit: u64,
+) -> Result<(), IdentityError> {
+    let remaining = valid_until.saturating_sub(exec::block_timestamp());
+    // rounded up and at least one block
+    let blocks = remaining.saturating_sub(1) / block_duration + 1;
+    msg::send_with_gas_delayed(
+        exec::program_id(),
+        IdentityAction::ExpireClaim { subject, piece_id },
+        gas_limit,
+        0,
+        u32::try_from(blocks).unwrap_or(u32::MAX),
+    )
+    .map(|_| ())
+    .map_err(|_| IdentityError::InsufficientGas)
+}
+
 impl IdentityStorage {
+    /// Checks the `signature` of the `message` and consumes the `key`'s nonce.
+    ///
+    /// # Requirements:
+    /// * `nonce` MUST be equal to the current `key`'s nonce.
+    /// * `signature` MUST be the `key`'s signature of the `message`.
+    fn authenticate(
+        &mut self,
+        key: PublicKey,
+        signature: &Signature,
+        nonce: u64,
+        message: &[u8],
+    ) -> Result<(), IdentityError> {
+        self.check_signature(&key, signature, nonce, message)?;
+        self.consume_nonce(key);
+
+        Ok(())
+    }
+
+    /// Checks the `signature` of the `message` without consuming the `key`'s nonce.
+    ///
+    /// # Requirements:
+    /// * `nonce` MUST be equal to the current `key`'s nonce.
+    /// * `signature` MUST be the `key`'s signature of the `message`.
+    fn check_signature(
+        &self,
+        key: &PublicKey,
+        signature: &Signature,
+        nonce: u64,
+        message: &[u8],
+    ) -> Result<(), IdentityError> {
+        if self.nonces.get(key).copied().unwrap_or_default() != nonce {
+            return Err(IdentityError::InvalidNonce);
+        }
+        if !crypto::verify(key, signature, message) {
+            return Err(IdentityError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+
+    /// Increments the `key`'s nonce.
+    fn consume_nonce(&mut self, key: PublicKey) {
+        *self.nonces.entry(key).or_default() += 1;
+    }
+
+    /// Checks that the message sender controls the `key`.
+    ///
+    /// # Requirements:
+    /// * for [`KeyProof::Signature`] the signature MUST be the `key`'s signature
+    ///   of the `message` built with the provided nonce, which is consumed.
+    /// * for [`KeyProof::LinkedActor`] the message sender MUST be linked to the `key`.
+    fn authorize(
+        &mut self,
+        key: PublicKey,
+        proof: KeyProof,
+        message: impl FnOnce(u64) -> Vec<u8>,
+    ) -> Result<(), IdentityError> {
+        match proof {
+            KeyProof::Signature { signature, nonce } => {
+                self.authenticate(key, &signature, nonce, &message(nonce))
+            }
+            KeyProof::LinkedActor => {
+                if !self.is_linked(&key, &msg::source()) {
+                    return Err(IdentityError::SenderNotLinked);
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn is_linked(&self, key: &PublicKey, actor: &ActorId) -> bool {
+        matches!(self.key_actors.get(key), Some(actors) if actors.contains(actor))
+    }
+
+    fn is_trusted(&self, issuer: &PublicKey) -> bool {
+        matches!(self.issuers.get(issuer), Some(info) if info.trusted)
+    }
+
+    /// Returns the shortest chain of issuers from the claim's issuer to the `root`
+    /// through at most `max_depth` accreditations of the claim's type.
+    fn accreditation_chain(
+        &self,
+        subject: &PublicKey,
+        piece_id: PieceId,
+        root: PublicKey,
+        max_depth: u32,
+    ) -> Option<Vec<PublicKey>> {
+        let claim = self.claim(subject, piece_id).ok()?;
+        let timestamp = exec::block_timestamp();
+        let mut visited: HashSet<PublicKey> = HashSet::from_iter([claim.issuer]);
+        let mut chains = vec![vec![claim.issuer]];
+        // Every issuer but the root holds a claim and appears once in a chain.
+        let max_depth = max_depth.min(self.user_claims.len() as u32);
+
+        for depth in 0..=max_depth {
+            if chains.is_empty() {
+                break;
+            }
+            let mut next_chains = vec![];
+            for chain in chains {
+                let issuer = chain[chain.len() - 1];
+                if issuer == root {
+                    return Some(chain);
+                }
+                if depth == max_depth {
+                    continue;
+                }
+                let accreditations = self
+                    .user_claims
+                    .get(&issuer)
+                    .into_iter()
+                    .flat_map(|claims| claims.values());
+                for accreditation in accreditations {
+                    if accreditation
+                        .data
+                        .accredited_types
+                        .contains(&claim.data.schema_id)
+                        && accreditation.data.status_at(timestamp) == ClaimStatus::Active
+                        && visited.insert(accreditation.issuer)
+                    {
+                        let mut next_chain = chain.clone();
+                        next_chain.push(accreditation.issuer);
+                        next_chains.push(next_chain);
+                    }
+                }
+            }
+            chains = next_chains;
+        }
+
+        None
+    }
+
+    /// Returns the hash of the claim's `attribute` labelled by the claim's schema.
+    fn attribute_hash(&self, claim: &Claim, attribute: &str) -> Option<[u8; 32]> {
+        let schema = self.schemas.get(&claim.data.schema_id)?;
+        let index = schema
+            .attributes
+            .iter()
+            .position(|name| name == attribute)?;
+        match &claim.data.hashed_info {
+            HashedInfo::List(hashes) => hashes.get(index).copied(),
+            HashedInfo::MerkleRoot { .. } => None,
+        }
+    }
+
+    /// Checks whether the `hash` is in the claim's hashed_info list, the claim is `Active`
+    /// and, if `trusted_only` is set, the claim's issuer is trusted.
+    fn check_claim(
+        &self,
+        subject: &PublicKey,
+        piece_id: PieceId,
+        hash: &[u8; 32],
+        trusted_only: bool,
+    ) -> bool {
+        match self.claim(subject, piece_id) {
+            Ok(claim) => {
+                matches!(&claim.data.hashed_info, HashedInfo::List(hashes) if hashes.contains(hash))
+                    && claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active
+                    && (!trusted_only || self.is_trusted(&claim.issuer))
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Checks whether the `leaf` is the hash of the claim's `attribute`
+    /// in the [`HashedInfo::MerkleRoot`] mode and the claim is `Active`.
+    fn check_merkle_attribute(
+        &self,
+        subject: &PublicKey,
+        piece_id: PieceId,
+        attribute: &str,
+        leaf: &[u8; 32],
+        path: &[[u8; 32]],
+    ) -> bool {
+        let claim = match self.claim(subject, piece_id) {
+            Ok(claim) => claim,
+            Err(_) => return false,
+        };
+        let (root, leaf_count) = match &claim.data.hashed_info {
+            HashedInfo::MerkleRoot { root, leaf_count } => (root, *leaf_count),
+            HashedInfo::List(_) => return false,
+        };
+        let index = self
+            .schemas
+            .get(&claim.data.schema_id)
+            .and_then(|schema| schema.attributes.iter().position(|name| name == attribute));
+
+        matches!(index, Some(index) if verify_merkle_path(root, leaf_count, index as u32, leaf, path))
+            && claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active
+    }
+
+    fn claim(&self, subject: &PublicKey, piece_id: PieceId) -> Result<&Claim, IdentityError> {
+        self.user_claims
+            .get(subject)
+            .and_then(|claims| claims.get(&piece_id))
+            .ok_or(IdentityError::ClaimNotFound)
+    }
+
+    /// Checks whether the claim is `Active` and at least the policy threshold
+    /// of the policy's verifiers verified the claim and haven't withdrawn the verification.
+    fn is_sufficiently_verified(&self, subject: &PublicKey, piece_id: PieceId) -> bool {
+        let (policy, claim) = match (
+            self.verification_policies.get(&(*subject, piece_id)),
+            self.claim(subject, piece_id),
+        ) {
+            (Some(policy), Ok(claim))
+                if claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active =>
+            {
+                (policy, claim)
+            }
+            _ => return false,
+        };
+        let verifications = claim
+            .verifiers
+            .iter()
+            .filter(|verification| {
+                (policy.verifiers.is_empty() || policy.verifiers.contains(&verification.verifier))
+                    && !self
+                        .withdrawals
+                        .contains_key(&(*subject, piece_id, verification.verifier))
+            })
+            .count();
+
+        verifications >= policy.threshold as usize
+    }
+
+    /// Sends [`IdentityEvent::QuorumReached`] to the zero address
+    /// if the claim satisfies its verification policy for the first time.
+    fn notify_quorum(&mut self, subject: PublicKey, piece_id: PieceId) {
+        if self.quorums.contains(&(subject, piece_id))
+            || !self.is_sufficiently_verified(&subject, piece_id)
+        {
+            return;
+        }
+        self.quorums.insert((subject, piece_id));
+        // a failed broadcast mustn't fail the claim change
+        let _ = msg::send(
+            ActorId::zero(),
+            IdentityEvent::QuorumReached { subject, piece_id },
+            0,
+        );
+    }
+
+    /// Returns the ids of the subject's claims satisfying the relying-party policy in the ascending order.
+    fn satisfying_claims(&self, subject: &PublicKey, policy: &RelyingPartyPolicy) -> Vec<PieceId> {
+        let now = exec::block_timestamp();
+        let mut piece_ids: Vec<PieceId> = self
+            .user_claims
+            .get(subject)
+            .into_iter()
+            .flatten()
+            .filter(|(piece_id, claim)| {
+                let verifications = claim
+                    .verifiers
+                    .iter()
+                    .filter(|verification| {
+                        !self.withdrawals.contains_key(&(
+                            *subject,
+                            **piece_id,
+                            verification.verifier,
+                        ))
+                    })
+                    .count();
+                let fresh = match policy.max_age {
+                    Some(max_age) => now.saturating_sub(claim.issuance.block_timestamp) <= max_age,
+                    None => true,
+                };
+
+                claim.data.schema_id == policy.schema_id
+                    && (policy.issuers.is_empty() || policy.issuers.contains(&claim.issuer))
+                    && verifications >= policy.min_verifiers as usize
+                    && fresh
+                    && claim.data.status_at(now) == ClaimStatus::Active
+            })
+            .map(|(piece_id, _)| *piece_id)
+            .collect();
+        piece_ids.sort_unstable();
+        piece_ids
+    }
+
+    /// Returns the public keys of all the claim's verifiers.
+    fn verifiers(&self, subject: &PublicKey, piece_id: PieceId) -> Vec<PublicKey> {
+        match self.claim(subject, piece_id) {
+            Ok(claim) => claim
+                .verifiers
+                .iter()
+                .map(|verification| verification.verifier)
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    fn claim_mut(
+        &mut self,
+        subject: &PublicKey,
+        piece_id: PieceId,
+    ) -> Result<&mut Claim, IdentityError> {
+        self.user_claims
+            .get_mut(subject)
+            .and_then(|claims| claims.get_mut(&piece_id))
+            .ok_or(IdentityError::ClaimNotFound)
+    }
+
+    /// Links the public key to the message sender.
+    ///
+    /// # Requirements:
+    /// * `key` MUST NOT be linked to the sender yet.
+    /// * `signature` MUST be the `key`'s signature of the [`KeyLinkSigningPayload`].
+    /// * `nonce` MUST be equal to the current `key`'s nonce.
+    ///
+    /// # Arguments:
+    /// * `key` - the public key being linked.
+    /// * `signature` - the corresponding signature with the `key`.
+    /// * `nonce` - the key's nonce.
+    fn link_key(
+        &mut self,
+        key: PublicKey,
+        signature: Signature,
+        nonce: u64,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let actor = msg::source();
+        if self.is_linked(&key, &actor) {
+            return Err(IdentityError::KeyAlreadyLinked);
+        }
+        let payload = KeyLinkSigningPayload::new(exec::program_id(), key, actor, nonce);
+        self.authenticate(key, &signature, nonce, &payload.signing_bytes())?;
+
+        self.key_actors.entry(key).or_default().push(actor);
+        self.actor_keys.entry(actor).or_default().push(key);
+
+        Ok(IdentityEvent::KeyLinked { key, actor })
+    }
+
+    /// Unlinks the public key from the message sender.
+    ///
+    /// # Requirements:
+    /// * `key` MUST be linked to the sender.
+    ///
+    /// # Arguments:
+    /// * `key` - the public key being unlinked.
+    fn unlink_key(&mut self, key: PublicKey) -> Result<IdentityEvent, IdentityError> {
+        let actor = msg::source();
+        if !self.is_linked(&key, &actor) {
+            return Err(IdentityError::KeyNotLinked);
+        }
+
+        self.remove_link(key, actor);
+
+        Ok(IdentityEvent::KeyUnlinked { key, actor })
+    }
+
+    /// Unlinks the public key from the actor on behalf of the key holder.
+    ///
+    /// # Requirements:
+    /// * `key` MUST be linked to the `actor`.
+    /// * `signature` MUST be the `key`'s signature of the [`KeyUnlinkSigningPayload`].
+    /// * `nonce` MUST be equal to the current `key`'s nonce.
+    ///
+    /// # Arguments:
+    /// * `key` - the public key being unlinked.
+    /// * `actor` - the actor the key is unlinked from.
+    /// * `signature` - the corresponding signature with the `key`.
+    /// * `nonce` - the key's nonce.
+    fn unlink_actor(
+        &mut self,
+        key: PublicKey,
+        actor: ActorId,
+        signature: Signature,
+        nonce: u64,
+    ) -> Result<IdentityEvent, IdentityError> {
+        if !self.is_linked(&key, &actor) {
+            return Err(IdentityError::KeyNotLinked);
+        }
+        let payload = KeyUnlinkSigningPayload::new(exec::program_id(), key, actor, nonce);
+        self.authenticate(key, &signature, nonce, &payload.signing_bytes())?;
+
+        self.remove_link(key, actor);
+
+        Ok(IdentityEvent::KeyUnlinked { key, actor })
+    }
+
+    /// Removes the link between the key and the actor dropping the emptied entries.
+    fn remove_link(&mut self, key: PublicKey, actor: ActorId) {
+        if let Some(actors) = self.key_actors.get_mut(&key) {
+            actors.retain(|linked| linked != &actor);
+            if actors.is_empty() {
+                self.key_actors.remove(&key);
+            }
+        }
+        if let Some(keys) = self.actor_keys.get_mut(&actor) {
+            keys.retain(|linked| linked != &key);
+            if keys.is_empty() {
+                self.actor_keys.remove(&actor);
+            }
+        }
+    }
+
+    /// Switches the claim to `Expired` once its `valid_until` has passed.
+    ///
+    /// # Requirements:
+    /// * the claim MUST be `Pending`, `Active` or `Suspended`.
+    /// * the claim's `valid_until` MUST have passed.
+    ///   The expiry sent by the program itself too early is rescheduled
+    ///   with the rest of its gas and the block duration observed since the claim issuance.
+    ///
+    /// # Arguments:
+    /// * `subject`- the subject's public key.
+    /// * `piece_id` - claim's id.
+    fn expire_claim(
+        &mut self,
+        subject: PublicKey,
+        piece_id: PieceId,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let claim = self.claim(&subject, piece_id)?;
+        let old_status = claim.data.status;
+        if transition_roles(old_status, ClaimStatus::Expired).is_none() {
+            return Err(IdentityError::InvalidTransition);
+        }
+        match claim.data.valid_until {
+            Some(valid_until) if valid_until <= exec::block_timestamp() => {}
+            Some(valid_until) => {
+                let gas_limit = exec::gas_available().saturating_sub(EXPIRY_HANDLING_GAS);
+                if msg::source() == exec::program_id() && gas_limit >= EXPIRY_HANDLING_GAS {
+                    let blocks = exec::block_height().saturating_sub(claim.issuance.block_height);
+                    let block_duration = exec::block_timestamp()
+                        .saturating_sub(claim.issuance.block_timestamp)
+                        / u64::from(blocks.max(1));
+                    // the claim can still be expired manually if the rescheduling fails
+                    let _ = schedule_expiry(
+                        subject,
+                        piece_id,
+                        valid_until,
+                        block_duration.max(1),
+                        gas_limit,
+                    );
+                }
+                return Err(IdentityError::ClaimNotExpired);
+            }
+            None => return Err(IdentityError::ClaimNotExpired),
+        }
+
+        self.claim_mut(&subject, piece_id)?.data.status = ClaimStatus::Expired;
+
+        let event = IdentityEvent::ClaimExpired {
+            subject,
+            piece_id,
+            old_status,
+        };
+        // a failed broadcast mustn't fail the expiry
+        let _ = msg::send(ActorId::zero(), &event, 0);
+        self.notify_subscribers(subject, piece_id, &event);
+
+        Ok(event)
+    }
+
+    /// Subscribes the message sender to the target and reserves gas for the notifications.
+    ///
+    /// # Requirements:
+    /// * the sender MUST NOT exceed [`MAX_SUBSCRIPTIONS`] targets
+    ///   and [`MAX_RESERVED_NOTIFICATIONS`] reserved notifications.
+    /// * a new subscription MUST reserve at least one notification.
+    ///
+    /// # Arguments:
+    /// * `target` - the subscription target.
+    /// * `notifications` - the number of the notifications to reserve gas for.
+    fn subscribe(
+        &mut self,
+        target: SubscriptionTarget,
+        notifications: u32,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let subscriber = msg::source();
+        let (subscribed, targets, reserved) = match self.subscribers.get(&subscriber) {
+            Some(entry) => (
+                entry.targets.contains(&target),
+                entry.targets.len(),
+                entry.reservations.len() as u32,
+            ),
+            None => (false, 0, 0),
+        };
+        let target_subscribers = self
+            .target_subscribers
+            .get(&target)
+            .map_or(0, |subscribers| subscribers.len());
+        if (!subscribed
+            && (targets >= MAX_SUBSCRIPTIONS || target_subscribers >= MAX_TARGET_SUBSCRIBERS))
+            || reserved.saturating_add(notifications) > MAX_RESERVED_NOTIFICATIONS
+        {
+            return Err(IdentityError::SubscriptionLimitExceeded);
+        }
+        if !subscribed && notifications == 0 {
+            return Err(IdentityError::NoNotificationsReserved);
+        }
+        let mut reservations = Vec::with_capacity(notifications as usize);
+        for _ in 0..notifications {
+            match exec::reserve_gas(NOTIFICATION_GAS, NOTIFICATION_RESERVATION_BLOCKS) {
+                Ok(reservation) => reservations.push(reservation),
+                Err(_) => {
+                    for reservation in reservations {
+                        // a failed rollback mustn't hide the reservation error
+                        let _ = exec::unreserve_gas(reservation);
+                    }
+                    return Err(IdentityError::GasReservationFailed);
+                }
+            }
+        }
+
+        let entry = self.subscribers.entry(subscriber).or_default();
+        if !subscribed {
+            entry.targets.push(target);
+            self.target_subscribers
+                .entry(target)
+                .or_default()
+                .push(subscriber);
+        }
+        entry.reservations.extend(reservations);
+
+        Ok(IdentityEvent::Subscribed {
+            subscriber,
+            target,
+            reserved_notifications: entry.reservations.len() as u32,
+        })
+    }
+
+    /// Unsubscribes the message sender from the target.
+    /// The reserved gas is returned once the sender has no targets left.
+    ///
+    /// # Requirements:
+    /// * the sender MUST be subscribed to the target.
+    ///
+    /// # Arguments:
+    /// * `target` - the subscription target.
+    fn unsubscribe(&mut self, target: SubscriptionTarget) -> Result<IdentityEvent, IdentityError> {
+        let subscriber = msg::source();
+        let entry = self
+            .subscribers
+            .get_mut(&subscriber)
+            .filter(|entry| entry.targets.contains(&target))
+            .ok_or(IdentityError::NotSubscribed)?;
+
+        entry.targets.retain(|subscribed| subscribed != &target);
+        let unsubscribed = entry.targets.is_empty();
+        self.remove_target_subscriber(target, subscriber);
+        if unsubscribed {
+            if let Some(entry) = self.subscribers.remove(&subscriber) {
+                for reservation in entry.reservations {
+                    // an expired reservation mustn't keep the subscriber in the storage
+                    let _ = exec::unreserve_gas(reservation);
+                }
+            }
+        }
+
+        Ok(IdentityEvent::Unsubscribed { subscriber, target })
+    }
+
+    /// Removes the subscriber from the target's subscribers dropping the emptied entry.
+    fn remove_target_subscriber(&mut self, target: SubscriptionTarget, subscriber: ActorId) {
+        if let Some(subscribers) = self.target_subscribers.get_mut(&target) {
+            subscribers.retain(|subscribed| subscribed != &subscriber);
+            if subscribers.is_empty() {
+                self.target_subscribers.remove(&target);
+            }
+        }
+    }
+
+    /// Sends the `event` of the claim from the notification gas reservations
+    /// to every subscriber of the claim, its subject or its issuer.
+    /// Subscribers whose notifications run out are unsubscribed from all their targets.
+    fn notify_subscribers(&mut self, subject: PublicKey, piece_id: PieceId, event: &IdentityEvent) {
+        let issuer = match self.claim(&subject, piece_id) {
+            Ok(claim) => claim.issuer,
+            Err(_) => return,
+        };
+        let targets = [
+            SubscriptionTarget::Subject(subject),
+            SubscriptionTarget::Issuer(issuer),
+            SubscriptionTarget::Claim(subject, piece_id),
+        ];
+        let mut notified = HashSet::new();
+        let mut exhausted = vec![];
+        for target in targets {
+            for subscriber in self.target_subscribers.get(&target).into_iter().flatten() {
+                // a subscriber of several targets is notified once
+                if !notified.insert(*subscriber) {
+                    continue;
+                }
+                let entry = match self.subscribers.get_mut(subscriber) {
+                    Some(entry) => entry,
+                    None => continue,
+                };
+                if let Some(reservation) = entry.reservations.pop() {
+                    // an expired reservation mustn't fail the claim change
+                    let _ = msg::send_from_reservation(reservation, *subscriber, event, 0);
+                }
+                if entry.reservations.is_empty() {
+                    exhausted.push(*subscriber);
+                }
+            }
+        }
+        for subscriber in exhausted {
+            if let Some(entry) = self.subscribers.remove(&subscriber) {
+                for target in entry.targets {
+                    self.remove_target_subscriber(target, subscriber);
+                }
+            }
+        }
+    }
+
+    /// Registers a claim schema.
+    ///
+    /// # Requirements:
+    /// * `attributes` MUST be unique.
+    ///
+    /// # Arguments:
+    /// * `name` - the schema's name.
+    /// * `attributes` - the schema's ordered attribute names.
+    /// * `hash_algorithm` - the algorithm the attributes are hashed with.
+    fn register_schema(
+        &mut self,
+        name: String,
+        attributes: Vec<String>,
+        hash_algorithm: HashAlgorithm,
+    ) -> Result<IdentityEvent, IdentityError> {
+        if attributes
+            .iter()
+            .enumerate()
+            .any(|(i, attribute)| attributes[..i].contains(attribute))
+        {
+            return Err(IdentityError::InvalidSchema);
+        }
+
+        let schema_id = self.schema_counter;
+        self.schemas.insert(
+            schema_id,
+            Schema {
+                id: schema_id,
+                name,
+                attributes,
+                hash_algorithm,
+            },
+        );
+        self.schema_counter += 1;
+
+        Ok(IdentityEvent::SchemaRegistered { schema_id })
+    }
+
+    /// Registers a relying-party policy owned by the message sender.
+    ///
+    /// # Requirements:
+    /// * `schema_id` MUST be registered.
+    ///
+    /// # Arguments:
+    /// * `name` - the policy's name.
+    /// * `schema_id` - the required schema of the claims.
+    /// * `issuers` - the accepted issuers or none if every issuer is accepted.
+    /// * `min_verifiers` - the minimum number of the verifications that aren't withdrawn.
+    /// * `max_age` - the maximum time in milliseconds since the claim issuance block.
+    fn register_policy(
+        &mut self,
+        name: String,
+        schema_id: SchemaId,
+        issuers: Vec<PublicKey>,
+        min_verifiers: u32,
+        max_age: Option<u64>,
+    ) -> Result<IdentityEvent, IdentityError> {
+        if !self.schemas.contains_key(&schema_id) {
+            return Err(IdentityError::SchemaNotFound);
+        }
+
+        let policy_id = self.policy_counter;
+        self.policies.insert(
+            policy_id,
+            RelyingPartyPolicy {
+                id: policy_id,
+                owner: msg::source(),
+                name,
+                schema_id,
+                issuers,
+                min_verifiers,
+                max_age,
+            },
+        );
+        self.policy_counter += 1;
+
+        Ok(IdentityEvent::PolicyRegistered { policy_id })
+    }
+
+    /// Registers the issuer or updates its metadata. The issuer becomes untrusted.
+    ///
+    /// # Requirements:
+    /// * all the public keys and signatures MUST be non-zero.
+    /// * `proof` MUST prove the `issuer` key control
+    ///   (a signature MUST be made of the [`IssuerRegistrationSigningPayload`]).
+    ///
+    /// # Arguments:
+    /// * `issuer` - the issuer's public key.
+    /// * `proof` - the proof of the `issuer` key control.
+    /// * `metadata` - the issuer's metadata.
+    fn register_issuer(
+        &mut self,
+        issuer: PublicKey,
+        proof: KeyProof,
+        metadata: IssuerMetadata,
+    ) -> Result<IdentityEvent, IdentityError> {
+        self.authorize(issuer, proof, |nonce| {
+            IssuerRegistrationSigningPayload::new(
+                exec::program_id(),
+                issuer,
+                metadata.clone(),
+                nonce,
+            )
+            .signing_bytes()
+        })?;
+        self.issuers.insert(
+            issuer,
+            IssuerInfo {
+                metadata,
+                trusted: false,
+            },
+        );
+
+        Ok(IdentityEvent::IssuerRegistered { issuer })
+    }
+
+    /// Marks the issuer as trusted or untrusted.
+    ///
+    /// # Requirements:
+    /// * the message sender MUST be the admin.
+    /// * `issuer` MUST be registered.
+    ///
+    /// # Arguments:
+    /// * `issuer` - the issuer's public key.
+    /// * `trusted` - whether the issuer is trusted.
+    fn set_issuer_trust(
+        &mut self,
+        issuer: PublicKey,
+        trusted: bool,
+    ) -> Result<IdentityEvent, IdentityError> {
+        if msg::source() != self.admin {
+            return Err(IdentityError::NotAdmin);
+        }
+        self.issuers
+            .get_mut(&issuer)
+            .ok_or(IdentityError::IssuerNotRegistered)?
+            .trusted = trusted;
+
+        Ok(IdentityEvent::IssuerTrustChanged { issuer, trusted })
+    }
+
     /// Creates a new claim.
     ///
     /// # Requirements:
     /// * all the public keys and signatures MUST be non-zero.
+    /// * `issuer_signature` MUST be the `issuer`'s signature of the [`ClaimSigningPayload`].
+    /// * `nonce` MUST be equal to the current `issuer`'s nonce.
+    /// * `data` status MUST be either `Pending` or `Active`.
+    /// * `data` MUST hold a hash for each attribute of its schema.
+    /// * `data` `valid_until` MUST be in the future and after `valid_from`.
+    ///   The claim expiry is scheduled with [`IdentityAction::ExpireClaim`], so the message
+    ///   MUST have at least [`EXPIRY_GAS`] and [`EXPIRY_HANDLING_GAS`] of gas available.
+    /// * `data` `issuance_date` MUST NOT be more than [`MAX_ISSUANCE_DATE_DRIFT`] in the future.
     ///
     /// # Arguments:
     /// * `issuer` - the claim issuer's public key.
     /// * `issuer_signature` - the corresponding signature with the `issuer` public key.
     /// * `subject`- the subject's public key.
     /// * `data` - claim's data.
+    /// * `nonce` - the issuer's nonce.
     fn issue_claim(
         &mut self,
         issuer: PublicKey,
         issuer_signature: Signature,
         subject: PublicKey,
         data: ClaimData,
-    ) {
+        nonce: u64,
+    ) -> Result<IdentityEvent, IdentityError> {
+        if !matches!(data.status, ClaimStatus::Pending | ClaimStatus::Active) {
+            return Err(IdentityError::InvalidInitialStatus);
+        }
+        let schema = self
+            .schemas
+            .get(&data.schema_id)
+            .ok_or(IdentityError::SchemaNotFound)?;
+        if schema.attributes.len() != data.hashed_info.attribute_count() {
+            return Err(IdentityError::SchemaMismatch);
+        }
+        let block_timestamp = exec::block_timestamp();
+        if data.issuance_date > block_timestamp.saturating_add(MAX_ISSUANCE_DATE_DRIFT) {
+            return Err(IdentityError::IssuanceDateInFuture);
+        }
+        if let Some(valid_until) = data.valid_until {
+            if valid_until <= block_timestamp
+                || matches!(data.valid_from, Some(valid_from) if valid_from >= valid_until)
+            {
+                return Err(IdentityError::InvalidValidityPeriod);
+            }
+            if exec::gas_available() < EXPIRY_GAS + EXPIRY_HANDLING_GAS {
+                return Err(IdentityError::InsufficientGas);
+            }
+        }
+        let payload =
+            ClaimSigningPayload::new(exec::program_id(), subject, issuer, data.clone(), nonce);
+        self.check_signature(&issuer, &issuer_signature, nonce, &payload.signing_bytes())?;
+
+        let piece_id = self.piece_counter;
+        if let Some(valid_until) = data.valid_until {
+            schedule_expiry(subject, piece_id, valid_until, BLOCK_DURATION, EXPIRY_GAS)?;
+        }
+        // the nonce is consumed only once the expiry is scheduled
+        self.consume_nonce(issuer);
+
         self.user_claims.entry(subject).or_default().insert(
-            self.piece_counter,
+            piece_id,
             Claim {
                 issuer,
                 issuer_signature,
                 subject,
                 verifiers: vec![],
+                issuance: IssuanceDate {
+                    asserted: data.issuance_date,
+                    block_timestamp,
+                    block_height: exec::block_height(),
+                },
                 data,
             },
         );
+        self.piece_counter += 1;
 
-        msg::reply(
-            IdentityEvent::ClaimIssued {
-                issuer,
-                subject,
-                piece_id: self.piece_counter,
-            },
-            0,
-        )
-        .expect("IDENTITY: Error during replying with IdentityEvent::ClaimIssued");
+        let event = IdentityEvent::ClaimIssued {
+            issuer,
+            subject,
+            piece_id,
+        };
+        self.notify_subscribers(subject, piece_id, &event);
 
-        self.piece_counter += 1;
+        Ok(event)
     }
 
-    /// Changes claim's validation status.
+    /// Changes claim's status.
     ///
     /// # Requirements:
     /// * all the public keys and signatures MUST be non-zero.
+    /// * `validator` MUST be the claim's issuer or subject key or the admin.
+    /// * the transition MUST be allowed for the `validator`'s role (see [`ClaimStatus`]).
+    /// * a key `validator` MUST prove its control
+    ///   (a signature MUST be made of the [`StatusChangeSigningPayload`]).
     ///
     /// # Arguments:
-    /// * `validator` - the claim issuer's or subject's public key.
+    /// * `validator` - the claim issuer's or subject's key or the admin.
     /// * `subject`- the subject's public key.
     /// * `piece_id` - claim's id.
     /// * `status` - new claim's status.
     fn change_validation_status(
         &mut self,
-        validator: PublicKey,
+        validator: Authority,
         subject: PublicKey,
         piece_id: PieceId,
-        status: bool,
-    ) {
-        let data_piece = self
-            .user_claims
-            .get(&subject)
-            .expect("The user has no claims")
-            .get(&piece_id)
-            .expect("The user has not such claim with the provided piece_id");
-        if data_piece.subject != validator && data_piece.issuer != validator {
-            panic!("IDENTITY: You can not change this claim");
+        status: ClaimStatus,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let data_piece = self.claim(&subject, piece_id)?;
+        let roles = match validator {
+            Authority::Key { key, .. } => {
+                let mut roles = vec![];
+                if data_piece.issuer == key {
+                    roles.push(Role::Issuer);
+                }
+                if data_piece.subject == key {
+                    roles.push(Role::Subject);
+                }
+                if roles.is_empty() {
+                    return Err(IdentityError::NotSubjectOrIssuer);
+                }
+                roles
+            }
+            Authority::Admin => {
+                if msg::source() != self.admin {
+                    return Err(IdentityError::NotAdmin);
+                }
+                vec![Role::Admin]
+            }
+        };
+        let old_status = data_piece.data.status;
+        if old_status == ClaimStatus::Revoked {
+            return Err(IdentityError::ClaimRevoked);
         }
-        self.user_claims
-            .entry(subject)
-            .or_default()
-            .entry(piece_id)
-            .and_modify(|claim| claim.data.valid = status);
-
-        msg::reply(
-            IdentityEvent::ClaimValidationChanged {
-                validator,
+        let allowed =
+            transition_roles(old_status, status).ok_or(IdentityError::InvalidTransition)?;
+        if !roles.iter().any(|role| allowed.contains(role)) {
+            return Err(IdentityError::TransitionNotPermitted);
+        }
+        let validator = match validator {
+            Authority::Key { key, proof } => {
+                self.authorize(key, proof, |nonce| {
+                    StatusChangeSigningPayload::new(
+                        exec::program_id(),
+                        key,
+                        subject,
+                        piece_id,
+                        status,
+                        nonce,
+                    )
+                    .signing_bytes()
+                })?;
+                Some(key)
+            }
+            Authority::Admin => None,
+        };
+        self.claim_mut(&subject, piece_id)?.data.status = status;
+        // an activated claim may already hold enough verifications
+        self.notify_quorum(subject, piece_id);
+
+        let event = IdentityEvent::ClaimValidationChanged {
+            validator,
+            subject,
+            piece_id,
+            old_status,
+            new_status: status,
+        };
+        self.notify_subscribers(subject, piece_id, &event);
+
+        Ok(event)
+    }
+
+    /// Permanently revokes the claim.
+    ///
+    /// # Requirements:
+    /// * all the public keys and signatures MUST be non-zero.
+    /// * `issuer` MUST be the claim's issuer.
+    /// * the claim MUST NOT be revoked already.
+    /// * `proof` MUST prove the `issuer` key control
+    ///   (a signature MUST be made of the [`RevocationSigningPayload`]).
+    ///
+    /// # Arguments:
+    /// * `issuer` - the claim issuer's public key.
+    /// * `proof` - the proof of the `issuer` key control.
+    /// * `subject`- the subject's public key.
+    /// * `piece_id` - claim's id.
+    /// * `reason` - reason of the revocation.
+    fn revoke_claim(
+        &mut self,
+        issuer: PublicKey,
+        proof: KeyProof,
+        subject: PublicKey,
+        piece_id: PieceId,
+        reason: RevocationReason,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let claim = self.claim(&subject, piece_id)?;
+        if claim.issuer != issuer {
+            return Err(IdentityError::NotIssuer);
+        }
+        let old_status = claim.data.status;
+        if old_status == ClaimStatus::Revoked {
+            return Err(IdentityError::ClaimRevoked);
+        }
+        self.authorize(issuer, proof, |nonce| {
+            RevocationSigningPayload::new(
+                exec::program_id(),
+                issuer,
                 subject,
                 piece_id,
-                status,
-            },
-            0,
-        )
-        .expect("IDENTITY: Error during replying with IdentityEvent::ClaimValidationChanged");
+                reason,
+                nonce,
+            )
+            .signing_bytes()
+        })?;
+
+        let timestamp = exec::block_timestamp();
+        self.claim_mut(&subject, piece_id)?.data.status = ClaimStatus::Revoked;
+        self.revocations
+            .insert((subject, piece_id), Revocation { reason, timestamp });
+
+        let event = IdentityEvent::ClaimRevoked {
+            issuer,
+            subject,
+            piece_id,
+            old_status,
+            reason,
+            timestamp,
+        };
+        self.notify_subscribers(subject, piece_id, &event);
+
+        Ok(event)
+    }
+
+    /// Sets the verification policy of the claim.
+    ///
+    /// # Requirements:
+    /// * all the public keys and signatures MUST be non-zero.
+    /// * `owner` MUST be the claim's subject or issuer.
+    /// * the claim MUST NOT be revoked.
+    /// * `policy` threshold MUST be positive and MUST NOT exceed the number of the listed verifiers.
+    /// * `proof` MUST prove the `owner` key control
+    ///   (a signature MUST be made of the [`VerificationPolicySigningPayload`]).
+    ///
+    /// # Arguments:
+    /// * `owner` - the claim subject's or issuer's public key.
+    /// * `proof` - the proof of the `owner` key control.
+    /// * `subject`- the subject's public key.
+    /// * `piece_id` - claim's id.
+    /// * `policy` - the verification policy.
+    fn set_verification_policy(
+        &mut self,
+        owner: PublicKey,
+        proof: KeyProof,
+        subject: PublicKey,
+        piece_id: PieceId,
+        policy: VerificationPolicy,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let claim = self.claim(&subject, piece_id)?;
+        if claim.subject != owner && claim.issuer != owner {
+            return Err(IdentityError::NotSubjectOrIssuer);
+        }
+        if claim.data.status == ClaimStatus::Revoked {
+            return Err(IdentityError::ClaimRevoked);
+        }
+        if policy.threshold == 0
+            || (!policy.verifiers.is_empty() && policy.threshold as usize > policy.verifiers.len())
+        {
+            return Err(IdentityError::InvalidPolicy);
+        }
+        self.authorize(owner, proof, |nonce| {
+            VerificationPolicySigningPayload::new(
+                exec::program_id(),
+                owner,
+                subject,
+                piece_id,
+                policy.clone(),
+                nonce,
+            )
+            .signing_bytes()
+        })?;
+
+        // the quorum of the new policy is reported anew
+        self.quorums.remove(&(subject, piece_id));
+        self.verification_policies
+            .insert((subject, piece_id), policy.clone());
+        self.notify_quorum(subject, piece_id);
+
+        Ok(IdentityEvent::VerificationPolicySet {
+            owner,
+            subject,
+            piece_id,
+            policy,
+        })
     }
 
     /// Verifies the claim.
@@ -105,44 +1133,142 @@ impl IdentityStorage {
     /// # Requirements:
     /// * all the public keys and signatures MUST be non-zero.
     /// * `verifier` - MUST differ from the claim's subject or issuer.
+    /// * `verifier` - MUST NOT have verified the claim already, even if it withdrew the verification.
+    /// * `verifier_signature` MUST be the `verifier`'s signature of the [`VerificationSigningPayload`].
+    /// * `nonce` MUST be equal to the current `verifier`'s nonce.
     ///
     /// # Arguments:
     /// * `verifier` - the claim verifier's public key.
     /// * `verifier_signature` - the corresponding signature with the `verifier` public key.
     /// * `piece_id` - claim's id.
     /// * `subject` - subject's public key.
+    /// * `statement` - the verifier's statement about the claim.
+    /// * `nonce` - the verifier's nonce.
     fn verify_claim(
         &mut self,
         verifier: PublicKey,
         verifier_signature: Signature,
         subject: PublicKey,
         piece_id: PieceId,
-    ) {
-        let piece = self
-            .user_claims
-            .get(&subject)
-            .expect("The user has no claims")
-            .get(&piece_id)
-            .expect("The user has not such claim with the provided piece_id");
+        statement: String,
+        nonce: u64,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let piece = self.claim(&subject, piece_id)?;
         if piece.issuer == verifier || piece.subject == verifier {
-            panic!("IDENTITY: You can not verify this claim");
+            return Err(IdentityError::VerifierIsSubjectOrIssuer);
         }
-        self.user_claims
-            .entry(subject)
-            .or_default()
-            .entry(piece_id)
-            .and_modify(|claim| {
-                claim.verifiers.push((verifier, verifier_signature));
+        if self
+            .withdrawals
+            .contains_key(&(subject, piece_id, verifier))
+        {
+            return Err(IdentityError::VerificationWithdrawn);
+        }
+        if piece
+            .verifiers
+            .iter()
+            .any(|verification| verification.verifier == verifier)
+        {
+            return Err(IdentityError::AlreadyVerified);
+        }
+        let payload = VerificationSigningPayload::new(
+            exec::program_id(),
+            verifier,
+            subject,
+            piece_id,
+            piece.hash(),
+            statement.clone(),
+            nonce,
+        );
+        self.authenticate(
+            verifier,
+            &verifier_signature,
+            nonce,
+            &payload.signing_bytes(),
+        )?;
+        self.claim_mut(&subject, piece_id)?
+            .verifiers
+            .push(Verification {
+                verifier,
+                signature: verifier_signature,
+                statement,
             });
-        msg::reply(
-            IdentityEvent::VerifiedClaim {
+        self.notify_quorum(subject, piece_id);
+
+        let event = IdentityEvent::VerifiedClaim {
+            verifier,
+            subject,
+            piece_id,
+        };
+        self.notify_subscribers(subject, piece_id, &event);
+
+        Ok(event)
+    }
+
+    /// Withdraws the verification of the claim.
+    ///
+    /// # Requirements:
+    /// * all the public keys and signatures MUST be non-zero.
+    /// * `verifier` MUST be the claim's verifier.
+    /// * the verification MUST NOT be withdrawn already.
+    /// * `proof` MUST prove the `verifier` key control
+    ///   (a signature MUST be made of the [`WithdrawalSigningPayload`]).
+    ///
+    /// # Arguments:
+    /// * `verifier` - the claim verifier's public key.
+    /// * `proof` - the proof of the `verifier` key control.
+    /// * `subject`- the subject's public key.
+    /// * `piece_id` - claim's id.
+    /// * `reason` - reason of the withdrawal.
+    fn withdraw_verification(
+        &mut self,
+        verifier: PublicKey,
+        proof: KeyProof,
+        subject: PublicKey,
+        piece_id: PieceId,
+        reason: WithdrawalReason,
+    ) -> Result<IdentityEvent, IdentityError> {
+        let claim = self.claim(&subject, piece_id)?;
+        if !claim
+            .verifiers
+            .iter()
+            .any(|verification| verification.verifier == verifier)
+        {
+            return Err(IdentityError::NotVerifier);
+        }
+        if self
+            .withdrawals
+            .contains_key(&(subject, piece_id, verifier))
+        {
+            return Err(IdentityError::VerificationWithdrawn);
+        }
+        self.authorize(verifier, proof, |nonce| {
+            WithdrawalSigningPayload::new(
+                exec::program_id(),
                 verifier,
                 subject,
                 piece_id,
-            },
-            0,
-        )
-        .expect("IDENTITY: Error during replying with IdentityEvent::VerifiedClaim");
+                reason,
+                nonce,
+            )
+            .signing_bytes()
+        })?;
+
+        let timestamp = exec::block_timestamp();
+        self.withdrawals.insert(
+            (subject, piece_id, verifier),
+            Withdrawal { reason, timestamp },
+        );
+
+        let event = IdentityEvent::VerificationWithdrawn {
+            verifier,
+            subject,
+            piece_id,
+            reason,
+            timestamp,
+        };
+        self.notify_subscribers(subject, piece_id, &event);
+
+        Ok(event)
     }
 }
 
@@ -150,6 +1276,7 @@ impl IdentityStorage {
 extern "C" fn init() {
     let id_storage = IdentityStorage {
         piece_counter: 0,
+        admin: msg::source(),
         ..Default::default()
     };
     unsafe {
@@ -161,13 +1288,14 @@ extern "C" fn init() {
 async fn main() {
     let action: IdentityAction = msg::load().expect("Unable to decode IdentityAction");
     let identity = unsafe { IDENTITY.get_or_insert(Default::default()) };
-    match action {
+    let reply = match action {
         IdentityAction::IssueClaim {
             issuer,
             issuer_signature,
             subject,
             data,
-        } => identity.issue_claim(issuer, issuer_signature, subject, data),
+            nonce,
+        } => identity.issue_claim(issuer, issuer_signature, subject, data, nonce),
         IdentityAction::ChangeClaimValidationStatus {
             validator,
             subject,
@@ -179,8 +1307,122 @@ async fn main() {
             verifier_signature,
             subject,
             piece_id,
-        } => identity.verify_claim(verifier, verifier_signature, subject, piece_id),
-    }
+            statement,
+            nonce,
+        } => identity.verify_claim(
+            verifier,
+            verifier_signature,
+            subject,
+            piece_id,
+            statement,
+            nonce,
+        ),
+        IdentityAction::RevokeClaim {
+            issuer,
+            proof,
+            subject,
+            piece_id,
+            reason,
+        } => identity.revoke_claim(issuer, proof, subject, piece_id, reason),
+        IdentityAction::WithdrawVerification {
+            verifier,
+            proof,
+            subject,
+            piece_id,
+            reason,
+        } => identity.withdraw_verification(verifier, proof, subject, piece_id, reason),
+        IdentityAction::SetVerificationPolicy {
+            owner,
+            proof,
+            subject,
+            piece_id,
+            policy,
+        } => identity.set_verification_policy(owner, proof, subject, piece_id, policy),
+        IdentityAction::CheckMerkleAttribute {
+            subject,
+            piece_id,
+            attribute,
+            leaf,
+            path,
+        } => Ok(IdentityEvent::MerkleAttributeChecked {
+            included: identity.check_merkle_attribute(&subject, piece_id, &attribute, &leaf, &path),
+            subject,
+            piece_id,
+            attribute,
+        }),
+        IdentityAction::RegisterSchema {
+            name,
+            attributes,
+            hash_algorithm,
+        } => identity.register_schema(name, attributes, hash_algorithm),
+        IdentityAction::RegisterPolicy {
+            name,
+            schema_id,
+            issuers,
+            min_verifiers,
+            max_age,
+        } => identity.register_policy(name, schema_id, issuers, min_verifiers, max_age),
+        IdentityAction::QueryClaimStatus { subject, piece_id } => {
+            Ok(IdentityEvent::ClaimStatusQueried {
+                subject,
+                piece_id,
+                status: identity
+                    .claim(&subject, piece_id)
+                    .ok()
+                    .map(|claim| claim.data.status_at(exec::block_timestamp())),
+            })
+        }
+        IdentityAction::QueryCheckClaim {
+            subject,
+            piece_id,
+            hash,
+            trusted_only,
+        } => Ok(IdentityEvent::ClaimChecked {
+            subject,
+            piece_id,
+            hash,
+            valid: identity.check_claim(&subject, piece_id, &hash, trusted_only),
+        }),
+        IdentityAction::QuerySatisfiesPolicy { subject, policy_id } => identity
+            .policies
+            .get(&policy_id)
+            .ok_or(IdentityError::PolicyNotFound)
+            .map(|policy| IdentityEvent::PolicySatisfied {
+                subject,
+                policy_id,
+                piece_ids: identity.satisfying_claims(&subject, policy),
+            }),
+        IdentityAction::RegisterIssuer {
+            issuer,
+            proof,
+            metadata,
+        } => identity.register_issuer(issuer, proof, metadata),
+        IdentityAction::SetIssuerTrust { issuer, trusted } => {
+            identity.set_issuer_trust(issuer, trusted)
+        }
+        IdentityAction::LinkKey {
+            key,
+            signature,
+            nonce,
+        } => identity.link_key(key, signature, nonce),
+        IdentityAction::UnlinkKey { key } => identity.unlink_key(key),
+        IdentityAction::UnlinkActor {
+            key,
+            actor,
+            signature,
+            nonce,
+        } => identity.unlink_actor(key, actor, signature, nonce),
+        IdentityAction::ExpireClaim { subject, piece_id } => {
+            identity.expire_claim(subject, piece_id)
+        }
+        IdentityAction::Subscribe {
+            target,
+            notifications,
+        } => identity.subscribe(target, notifications),
+        IdentityAction::Unsubscribe { target } => identity.unsubscribe(target),
+    };
+
+    msg::reply(reply, 0).expect("IDENTITY: Error during replying with the action result");
 }
 
 #[no_mangle]
@@ -188,11 +1430,17 @@ extern "C" fn meta_state() -> *mut [i32; 2] {
     let state: IdentityStateQuery = msg::load().expect("Unable to decode IdentityStateQuery");
     let identity = unsafe { IDENTITY.get_or_insert(Default::default()) };
     let reply = match state {
-        IdentityStateQuery::UserClaims(pkey) => {
-            IdentityStateReply::UserClaims(match identity.user_claims.get(&pkey) {
+        IdentityStateQuery::UserClaims(pkey, trusted_only) => {
+            let mut claims: Vec<(PieceId, Claim)> = match identity.user_claims.get(&pkey) {
                 None => vec![],
-                Some(claims) => Vec::from_iter(claims.clone().into_iter()),
-            })
+                Some(claims) => claims
+                    .iter()
+                    .filter(|(_, claim)| !trusted_only || identity.is_trusted(&claim.issuer))
+                    .map(|(piece_id, claim)| (*piece_id, claim.clone()))
+                    .collect(),
+            };
+            claims.sort_by_key(|(piece_id, _)| *piece_id);
+            IdentityStateReply::UserClaims(claims)
         }
         IdentityStateQuery::Claim(pkey, piece_id) => IdentityStateReply::Claim(
             identity
@@ -203,42 +1451,179 @@ extern "C" fn meta_state() -> *mut [i32; 2] {
                 .cloned(),
         ),
         IdentityStateQuery::ValidationStatus(pkey, piece_id) => {
-            let mut status = false;
-            if let Some(user_claim) = identity.user_claims.get(&pkey) {
-                if let Some(claim) = user_claim.get(&piece_id) {
-                    status = claim.data.valid
-                }
-            }
-            IdentityStateReply::ValidationStatus(status)
+            IdentityStateReply::ValidationStatus(
+                identity
+                    .claim(&pkey, piece_id)
+                    .ok()
+                    .map(|claim| claim.data.status_at(exec::block_timestamp())),
+            )
+        }
+        IdentityStateQuery::Date(pkey, piece_id) => IdentityStateReply::Date(
+            identity
+                .claim(&pkey, piece_id)
+                .ok()
+                .map(|claim| claim.issuance),
+        ),
+        IdentityStateQuery::Verifiers(pkey, piece_id) => {
+            let mut verifiers = identity.verifiers(&pkey, piece_id);
+            verifiers.retain(|verifier| {
+                !identity
+                    .withdrawals
+                    .contains_key(&(pkey, piece_id, *verifier))
+            });
+            IdentityStateReply::Verifiers(verifiers)
+        }
+        IdentityStateQuery::AllVerifiers(pkey, piece_id) => {
+            IdentityStateReply::AllVerifiers(identity.verifiers(&pkey, piece_id))
+        }
+        IdentityStateQuery::VerificationPolicy(pkey, piece_id) => {
+            IdentityStateReply::VerificationPolicy(
+                identity
+                    .verification_policies
+                    .get(&(pkey, piece_id))
+                    .cloned(),
+            )
         }
-        IdentityStateQuery::Date(pkey, piece_id) => {
-            let mut date: u64 = 0;
-            if let Some(user_claim) = identity.user_claims.get(&pkey) {
-                if let Some(claim) = user_claim.get(&piece_id) {
-                    date = claim.data.issuance_date
+        IdentityStateQuery::IsSufficientlyVerified(pkey, piece_id) => {
+            IdentityStateReply::IsSufficientlyVerified(
+                identity.is_sufficiently_verified(&pkey, piece_id),
+            )
+        }
+        IdentityStateQuery::Withdrawal(pkey, piece_id, verifier) => IdentityStateReply::Withdrawal(
+            identity
+                .withdrawals
+                .get(&(pkey, piece_id, verifier))
+                .copied(),
+        ),
+        IdentityStateQuery::CheckClaim(pkey, piece_id, hash, trusted_only) => {
+            let status = identity.check_claim(&pkey, piece_id, &hash, trusted_only);
+            IdentityStateReply::CheckedClaim(pkey, piece_id, status)
+        }
+        IdentityStateQuery::CheckClaimAttribute(pkey, piece_id, attribute, hash) => {
+            let status = match identity.claim(&pkey, piece_id) {
+                Ok(claim) => {
+                    identity.attribute_hash(claim, &attribute) == Some(hash)
+                        && claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active
                 }
-            }
-            IdentityStateReply::Date(date)
+                Err(_) => false,
+            };
+            IdentityStateReply::CheckedClaimAttribute(pkey, piece_id, attribute, status)
         }
-        IdentityStateQuery::Verifiers(pkey, piece_id) => {
-            let mut verifiers: Vec<PublicKey> = vec![];
-            if let Some(user_claim) = identity.user_claims.get(&pkey) {
-                if let Some(claim) = user_claim.get(&piece_id) {
-                    let (public_keys, _signatures): (Vec<PublicKey>, Vec<Signature>) =
-                        claim.verifiers.clone().into_iter().unzip();
-                    verifiers = public_keys;
+        IdentityStateQuery::CheckPresentation(pkey, piece_id, presentation) => {
+            let status = match identity.claim(&pkey, piece_id) {
+                Ok(claim) => {
+                    let commitment = identity.schemas.get(&claim.data.schema_id).map(|schema| {
+                        schema
+                            .hash_algorithm
+                            .commitment(&presentation.salt, &presentation.value)
+                    });
+                    commitment.is_some()
+                        && identity.attribute_hash(claim, &presentation.attribute) == commitment
+                        && claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active
                 }
-            }
-            IdentityStateReply::Verifiers(verifiers)
+                Err(_) => false,
+            };
+            IdentityStateReply::CheckedPresentation(pkey, piece_id, status)
         }
-        IdentityStateQuery::CheckClaim(pkey, piece_id, hash) => {
-            let mut status = false;
-            if let Some(user_claim) = identity.user_claims.get(&pkey) {
-                if let Some(claim) = user_claim.get(&piece_id) {
-                    status = claim.data.hashed_info.contains(&hash)
+        IdentityStateQuery::CheckThreshold(pkey, piece_id, proof) => {
+            let status = match identity.claim(&pkey, piece_id) {
+                Ok(claim) if proof.threshold <= MAX_THRESHOLD => {
+                    let commitment = identity.schemas.get(&claim.data.schema_id).map(|schema| {
+                        schema
+                            .hash_algorithm
+                            .hash_chain(&proof.hash, proof.threshold)
+                    });
+                    commitment.is_some()
+                        && identity.attribute_hash(claim, &proof.attribute) == commitment
+                        && claim.data.status_at(exec::block_timestamp()) == ClaimStatus::Active
                 }
-            }
-            IdentityStateReply::CheckedClaim(pkey, piece_id, status)
+                _ => false,
+            };
+            IdentityStateReply::CheckedThreshold(pkey, piece_id, status)
+        }
+        IdentityStateQuery::CheckMerkleAttribute(pkey, piece_id, attribute, leaf, path) => {
+            let status = identity.check_merkle_attribute(&pkey, piece_id, &attribute, &leaf, &path);
+            IdentityStateReply::CheckedMerkleAttribute(pkey, piece_id, attribute, status)
+        }
+        IdentityStateQuery::Nonce(pkey) => {
+            IdentityStateReply::Nonce(identity.nonces.get(&pkey).copied().unwrap_or_default())
+        }
+        IdentityStateQuery::KeyActors(pkey) => IdentityStateReply::KeyActors(
+            identity.key_actors.get(&pkey).cloned().unwrap_or_default(),
+        ),
+        IdentityStateQuery::ActorKeys(actor) => IdentityStateReply::ActorKeys(
+            identity.actor_keys.get(&actor).cloned().unwrap_or_default(),
+        ),
+        IdentityStateQuery::Subscription(actor) => match identity.subscribers.get(&actor) {
+            Some(entry) => IdentityStateReply::Subscription(
+                entry.targets.clone(),
+                entry.reservations.len() as u32,
+            ),
+            None => IdentityStateReply::Subscription(vec![], 0),
+        },
+        IdentityStateQuery::ExpiringClaims(pkey, period) => {
+            let deadline = exec::block_timestamp().saturating_add(period);
+            let mut claims: Vec<(PieceId, u64)> = identity
+                .user_claims
+                .get(&pkey)
+                .map(|claims| {
+                    claims
+                        .iter()
+                        .filter(|(_, claim)| claim.data.status != ClaimStatus::Revoked)
+                        .filter_map(|(piece_id, claim)| {
+                            claim
+                                .data
+                                .valid_until
+                                .filter(|valid_until| *valid_until <= deadline)
+                                .map(|valid_until| (*piece_id, valid_until))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            claims.sort_by_key(|(piece_id, valid_until)| (*valid_until, *piece_id));
+            IdentityStateReply::ExpiringClaims(claims)
+        }
+        IdentityStateQuery::Issuer(pkey) => {
+            IdentityStateReply::Issuer(identity.issuers.get(&pkey).cloned())
+        }
+        IdentityStateQuery::AccreditationChain(pkey, piece_id, root, max_depth) => {
+            IdentityStateReply::AccreditationChain(
+                identity.accreditation_chain(&pkey, piece_id, root, max_depth),
+            )
+        }
+        IdentityStateQuery::Schema(schema_id) => {
+            IdentityStateReply::Schema(identity.schemas.get(&schema_id).cloned())
+        }
+        IdentityStateQuery::Schemas => {
+            let mut schemas: Vec<Schema> = identity.schemas.values().cloned().collect();
+            schemas.sort_by_key(|schema| schema.id);
+            IdentityStateReply::Schemas(schemas)
+        }
+        IdentityStateQuery::ClaimsBySchema(schema_id) => {
+            let mut claims: Vec<(PieceId, Claim)> = identity
+                .user_claims
+                .values()
+                .flatten()
+                .filter(|(_, claim)| claim.data.schema_id == schema_id)
+                .map(|(piece_id, claim)| (*piece_id, claim.clone()))
+                .collect();
+            claims.sort_by_key(|(piece_id, _)| *piece_id);
+            IdentityStateReply::ClaimsBySchema(claims)
+        }
+        IdentityStateQuery::Policy(policy_id) => {
+            IdentityStateReply::Policy(identity.policies.get(&policy_id).cloned())
+        }
+        IdentityStateQuery::SatisfiesPolicy(pkey, policy_id) => {
+            IdentityStateReply::SatisfiesPolicy(
+                identity
+                    .policies
+                    .get(&policy_id)
+                    .map(|policy| identity.satisfying_claims(&pkey, policy))
+                    .unwrap_or_default(),
+            )
+        }
+        IdentityStateQuery::IsRevoked(pkey, piece_id) => {
+            IdentityStateReply::IsRevoked(identity.revocations.get(&(pkey, piece_id)).copied())
         }
     };
     gstd::util::to_leak_ptr(reply.encode())
@@ -250,7 +1635,7 @@ gstd::metadata! {
         input: InitIdentity,
     handle:
         input: IdentityAction,
-        output: IdentityEvent,
+        output: Result<IdentityEvent, IdentityError>,
     state:
         input: IdentityStateQuery,
         output: IdentityStateReply,
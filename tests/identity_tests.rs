@@ -1,23 +1,38 @@
-use gstd::prelude::*;
+use gstd::{prelude::*, ActorId};
 use gtest::System;
 use hex_literal::hex;
 use identity::io::*;
 use sha2::{Digest, Sha256};
-use sp_core::{sr25519::Pair as Sr25519Pair, Pair};
+use sp_core::{
+    ecdsa::Pair as EcdsaPair, ed25519::Pair as Ed25519Pair, keccak_256,
+    sr25519::Pair as Sr25519Pair, Pair,
+};
 mod utils;
 use utils::*;
 
 const USER: u64 = 10;
 const PIECE_ID: PieceId = 0;
 const DATE: u64 = 12288282;
+const SCHEMA_ID: SchemaId = 0;
+const STATEMENT: &str = "checked the passport in person";
 
 #[test]
 fn issue_claim_by_subject() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -25,36 +40,125 @@ fn issue_claim_by_subject() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
 }
 
 #[test]
 fn issue_claim_by_issuer() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
     let issuer_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
     ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
+    let claims = vec![(PIECE_ID, claim.clone())];
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
+}
+
+#[test]
+fn issue_claim_by_ed25519_issuer() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Ed25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Ed25519(issuer_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -62,33 +166,197 @@ fn issue_claim_by_issuer() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: issuer_pair.public().0,
-        issuer_signature: issuer_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer,
+        issuer_signature: Signature::Ed25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
+    let claims = vec![(PIECE_ID, claim.clone())];
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+}
+
+#[test]
+fn issue_claim_by_secp256k1_issuer() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = EcdsaPair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Secp256k1(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Secp256k1(
+            issuer_pair
+                .sign_prehashed(&ethereum_message_hash(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                )))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+
+    // the signature scheme differs from the issuer's key one
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Secp256k1(
+            issuer_pair
+                .sign_prehashed(&ethereum_message_hash(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                )))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID + 1,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // a hash signed without the `personal_sign` prefix isn't accepted
+    let signing_bytes = claim_signing_bytes(&id_program, subject, issuer, &claim_data, 1);
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Secp256k1(
+            issuer_pair.sign_prehashed(&keccak_256(&signing_bytes)).0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        1,
+        PIECE_ID + 1,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // a wrong recovery id recovers another key
+    let mut signature = issuer_pair
+        .sign_prehashed(&ethereum_message_hash(&signing_bytes))
+        .0;
+    signature[64] ^= 1;
+    let mut claim = Claim {
+        issuer,
+        issuer_signature: Signature::Secp256k1(signature),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim.clone(),
+        1,
+        PIECE_ID + 1,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // Ethereum wallets add 27 to the recovery id
+    signature[64] ^= 1;
+    signature[64] += 27;
+    claim.issuer_signature = Signature::Secp256k1(signature);
+    issue_claim_utils(&id_program, USER, claim.clone(), 1, PIECE_ID + 1, None);
+    check_claim_state_utils(&id_program, subject, PIECE_ID + 1, claim);
 }
 
 #[test]
 fn issue_multiple_claim() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "address",
+        &["city", "street"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -100,33 +368,228 @@ fn issue_multiple_claim() {
     let street = hasher.finalize().as_slice().try_into().expect("Wrong size");
 
     let claim_data = ClaimData {
-        hashed_info: Vec::from([city, street]),
+        hashed_info: HashedInfo::List(Vec::from([city, street])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
+}
+
+#[test]
+fn issue_claim_failures() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    // signed by the subject on behalf of the issuer
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // signature of other data
+    let mut forged_data = claim_data.clone();
+    forged_data.issuance_date = DATE + 1;
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, forged_data.issuance_date),
+        data: forged_data,
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // signature replayed onto another subject
+    let other_subject = PublicKey::Sr25519(
+        Sr25519Pair::from_seed(&hex!(
+            "9A61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+        ))
+        .public()
+        .0,
+    );
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject: other_subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // signature made for another deployment
+    let payload = ClaimSigningPayload::new(ActorId::zero(), subject, issuer, claim_data.clone(), 0);
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(issuer_pair.sign(&payload.signing_bytes()).0),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    // zero signature
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519([0; 64]),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_user_claims_state_utils(&id_program, subject, false, vec![]);
+    check_user_claims_state_utils(&id_program, other_subject, false, vec![]);
 }
 
 #[test]
 fn validation_status_from_subject() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -134,48 +597,92 @@ fn validation_status_from_subject() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
-    check_valid_state_utils(&id_program, subject_pair.public().0, PIECE_ID, true);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Active);
 
     validation_claim_utils(
         &id_program,
         USER,
-        subject_pair.public().0,
-        subject_pair.public().0,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Suspended,
+                            1,
+                        ))
+                        .0,
+                ),
+                nonce: 1,
+            },
+        },
+        subject,
         PIECE_ID,
-        false,
-        false,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
     );
-    check_valid_state_utils(&id_program, subject_pair.public().0, PIECE_ID, false);
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Suspended);
 }
 
 #[test]
 fn validation_status_from_issuer() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
     let issuer_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
     ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -183,47 +690,91 @@ fn validation_status_from_issuer() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: issuer_pair.public().0,
-        issuer_signature: issuer_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
 
     validation_claim_utils(
         &id_program,
         USER,
-        issuer_pair.public().0,
-        subject_pair.public().0,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    issuer_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            issuer,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Suspended,
+                            1,
+                        ))
+                        .0,
+                ),
+                nonce: 1,
+            },
+        },
+        subject,
         PIECE_ID,
-        false,
-        false,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
     );
-    check_valid_state_utils(&id_program, subject_pair.public().0, PIECE_ID, false);
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Suspended);
 }
 
 #[test]
 fn validation_status_failures() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
     let issuer_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
     ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -231,57 +782,257 @@ fn validation_status_failures() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: issuer_pair.public().0,
-        issuer_signature: issuer_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data,
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
 
     // try to validate with the third key
     let third_key = Sr25519Pair::from_seed(&hex!(
         "9A61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
     ));
+    let third = PublicKey::Sr25519(third_key.public().0);
     validation_claim_utils(
         &id_program,
         USER,
-        third_key.public().0,
-        subject_pair.public().0,
+        Authority::Key {
+            key: third,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    third_key
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            third,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Suspended,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        subject,
         PIECE_ID,
-        false,
-        true,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::NotSubjectOrIssuer),
     );
     // validate wrong PIECE_ID
     validation_claim_utils(
         &id_program,
         USER,
-        subject_pair.public().0,
-        subject_pair.public().0,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID + 1,
+                            ClaimStatus::Suspended,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        subject,
         PIECE_ID + 1,
-        false,
-        true,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::ClaimNotFound),
     );
     // validate the user with no claims
     validation_claim_utils(
         &id_program,
         USER,
-        subject_pair.public().0,
-        third_key.public().0,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            third,
+                            PIECE_ID,
+                            ClaimStatus::Suspended,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        third,
         PIECE_ID,
-        false,
-        true,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::ClaimNotFound),
+    );
+    // the subject's key signed by the third key
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    third_key
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Suspended,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::InvalidSignature),
+    );
+    // the signature of another status
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    issuer_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            issuer,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            1,
+                        ))
+                        .0,
+                ),
+                nonce: 1,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Active);
+
+    let issuer_signature = Signature::Sr25519(
+        issuer_pair
+            .sign(&status_change_signing_bytes(
+                &id_program,
+                issuer,
+                subject,
+                PIECE_ID,
+                ClaimStatus::Suspended,
+                1,
+            ))
+            .0,
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: issuer_signature,
+                nonce: 1,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    issuer_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            issuer,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            2,
+                        ))
+                        .0,
+                ),
+                nonce: 2,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Suspended,
+        ClaimStatus::Active,
+        None,
+    );
+    // replay the same message
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: issuer_signature,
+                nonce: 1,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::InvalidNonce),
     );
 }
 
@@ -289,9 +1040,19 @@ fn validation_status_failures() {
 fn verify_claim() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -299,52 +1060,86 @@ fn verify_claim() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data.clone(),
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
 
     let verifier_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
     verify_claim_utils(
         &id_program,
         USER,
-        verifier_pair.public().0,
-        verifier_pair.sign(claim_data.encode().as_slice()).0,
-        subject_pair.public().0,
-        PIECE_ID,
-        false,
-    );
-    check_verifiers_state_utils(
-        &id_program,
-        subject_pair.public().0,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         PIECE_ID,
-        vec![verifier_pair.public().0],
+        STATEMENT,
+        0,
+        None,
     );
+    check_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![verifier]);
 }
 
 #[test]
 fn verify_claim_failures() {
     let sys = System::new();
     let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
     let subject_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
 
     let mut hasher = Sha256::new();
     hasher.update(b"Amsterdam");
@@ -352,94 +1147,3781 @@ fn verify_claim_failures() {
     // read hash digest and consume hasher
     let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
     let claim_data = ClaimData {
-        hashed_info: Vec::from([result]),
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
         issuance_date: DATE,
-        valid: true,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
     };
 
     let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
         data: claim_data.clone(),
     };
 
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
     let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim.clone());
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
 
     let verifier_pair = Sr25519Pair::from_seed(&hex!(
         "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
     ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
     // verify user with no claims
     verify_claim_utils(
         &id_program,
         USER,
-        verifier_pair.public().0,
-        verifier_pair.sign(claim_data.encode().as_slice()).0,
-        verifier_pair.public().0,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    verifier,
+                    PIECE_ID,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+        ),
+        verifier,
         PIECE_ID,
-        true,
+        STATEMENT,
+        0,
+        Some(IdentityError::ClaimNotFound),
     );
     // verify wrong piece_id
 
     verify_claim_utils(
         &id_program,
         USER,
-        verifier_pair.public().0,
-        verifier_pair.sign(claim_data.encode().as_slice()).0,
-        subject_pair.public().0,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID + 1,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
         PIECE_ID + 1,
-        true,
+        STATEMENT,
+        0,
+        Some(IdentityError::ClaimNotFound),
     );
-}
-
-#[test]
-fn check_claim() {
-    let sys = System::new();
-    let id_program = init_identity(&sys, USER);
-    let subject_pair = Sr25519Pair::from_seed(&hex!(
-        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
-    ));
-
-    let mut hasher = Sha256::new();
-    hasher.update(b"Amsterdam");
-
-    // read hash digest and consume hasher
-    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
-    let mut hasher = Sha256::new();
-    hasher.update(b"Nikolskaya");
-    let street = hasher.finalize().as_slice().try_into().expect("Wrong size");
-
-    let claim_data = ClaimData {
-        hashed_info: Vec::from([city, street]),
-        issuance_date: DATE,
-        valid: true,
-    };
-
-    let claim = Claim {
-        issuer: subject_pair.public().0,
-        issuer_signature: subject_pair.sign(claim_data.encode().as_slice()).0,
-        subject: subject_pair.public().0,
-        verifiers: vec![],
-        data: claim_data,
-    };
-
-    issue_claim_utils(&id_program, USER, claim.clone(), PIECE_ID, false);
-    let claims = vec![(PIECE_ID, claim.clone())];
-    check_user_claims_state_utils(&id_program, subject_pair.public().0, claims);
-    check_claim_state_utils(&id_program, subject_pair.public().0, PIECE_ID, claim);
-    check_date_state_utils(&id_program, subject_pair.public().0, PIECE_ID, DATE);
-
-    check_claim_hash_state_utils(&id_program, subject_pair.public().0, PIECE_ID, city, true);
-    check_claim_hash_state_utils(
+    // verify own claim
+    verify_claim_utils(
         &id_program,
-        subject_pair.public().0,
+        USER,
+        subject,
+        Signature::Sr25519(
+            subject_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    1,
+                ))
+                .0,
+        ),
+        subject,
         PIECE_ID,
-        [0; 32],
-        false,
+        STATEMENT,
+        1,
+        Some(IdentityError::VerifierIsSubjectOrIssuer),
+    );
+    // the signature of another statement
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    "checked nothing",
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        Some(IdentityError::InvalidSignature),
+    );
+    // the signature of another claim data
+    let other_data = ClaimData {
+        issuance_date: DATE + 1,
+        ..claim_data
+    };
+    let other_hash = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519([0; 64]),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, other_data.issuance_date),
+        data: other_data,
+    }
+    .hash();
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(
+                    &VerificationSigningPayload::new(
+                        program_actor_id(&id_program),
+                        verifier,
+                        subject,
+                        PIECE_ID,
+                        other_hash,
+                        STATEMENT.into(),
+                        0,
+                    )
+                    .signing_bytes(),
+                )
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![]);
+
+    let verifier_signature = Signature::Sr25519(
+        verifier_pair
+            .sign(&verification_signing_bytes(
+                &id_program,
+                verifier,
+                subject,
+                PIECE_ID,
+                STATEMENT,
+                0,
+            ))
+            .0,
+    );
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        verifier_signature,
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        None,
+    );
+    // the status change doesn't affect the claim hash
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Expired,
+        None,
+    );
+    assert_eq!(claim_hash(&id_program, subject, PIECE_ID), claim.hash());
+    // verify the claim twice
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    1,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        1,
+        Some(IdentityError::AlreadyVerified),
+    );
+    check_claim_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        Claim {
+            verifiers: vec![Verification {
+                verifier,
+                signature: verifier_signature,
+                statement: STATEMENT.into(),
+            }],
+            data: ClaimData {
+                status: ClaimStatus::Expired,
+                ..claim.data
+            },
+            ..claim
+        },
+    );
+}
+
+#[test]
+fn check_claim() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "address",
+        &["city", "street"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let mut hasher = Sha256::new();
+    hasher.update(b"Nikolskaya");
+    let street = hasher.finalize().as_slice().try_into().expect("Wrong size");
+
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([city, street])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
+    let claims = vec![(PIECE_ID, claim.clone())];
+    check_user_claims_state_utils(&id_program, subject, false, claims);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, DATE));
+
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, false, true);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, [0; 32], false, false);
+}
+
+#[test]
+fn check_claim_attribute() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "address",
+        &["city", "street"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    register_schema_utils(
+        &id_program,
+        USER,
+        "passport",
+        &["name", "nationality"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID + 1,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+    let city: [u8; 32] = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let mut hasher = Sha256::new();
+    hasher.update(b"Nikolskaya");
+    let street: [u8; 32] = hasher.finalize().as_slice().try_into().expect("Wrong size");
+
+    // the same hashes are issued under both schemas
+    for (nonce, schema_id) in [SCHEMA_ID, SCHEMA_ID + 1].into_iter().enumerate() {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([city, street])),
+            schema_id,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer: subject,
+            issuer_signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        &claim_data,
+                        nonce as u64,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(
+            &id_program,
+            USER,
+            claim,
+            nonce as u64,
+            PIECE_ID + nonce as u128,
+            None,
+        );
+    }
+
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "city", city, true);
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "street", street, true);
+    // Amsterdam as the street
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "street", city, false);
+    // the schema has no such attribute
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "country", city, false);
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "", city, false);
+    // the attributes are labelled by the claim's own schema
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID + 1, "city", city, false);
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID + 1, "name", city, true);
+    // there is no claim to resolve the schema of
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID + 2, "city", city, false);
+}
+
+#[test]
+fn replay_protection() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+
+    check_nonce_state_utils(&id_program, issuer, 0);
+    issue_claim_utils(&id_program, USER, claim.clone(), 0, PIECE_ID, None);
+    check_nonce_state_utils(&id_program, issuer, 1);
+    // replay the same message
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim.clone(),
+        0,
+        PIECE_ID + 1,
+        Some(IdentityError::InvalidNonce),
+    );
+    // the same signature with the next nonce
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim.clone(),
+        1,
+        PIECE_ID + 1,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_user_claims_state_utils(&id_program, subject, false, vec![(PIECE_ID, claim.clone())]);
+
+    let claim = Claim {
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    1,
+                ))
+                .0,
+        ),
+        ..claim
+    };
+    issue_claim_utils(&id_program, USER, claim, 1, PIECE_ID + 1, None);
+    check_nonce_state_utils(&id_program, issuer, 2);
+
+    let verifier_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
+    let verifier_signature = Signature::Sr25519(
+        verifier_pair
+            .sign(&verification_signing_bytes(
+                &id_program,
+                verifier,
+                subject,
+                PIECE_ID,
+                STATEMENT,
+                0,
+            ))
+            .0,
+    );
+    // a nonce from the future
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    1,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        1,
+        Some(IdentityError::InvalidNonce),
+    );
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        verifier_signature,
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        None,
+    );
+    // replay the same message
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        verifier_signature,
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        Some(IdentityError::AlreadyVerified),
+    );
+    // the signature of another claim
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    1,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID + 1,
+        STATEMENT,
+        1,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_nonce_state_utils(&id_program, verifier, 1);
+    check_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![verifier]);
+}
+
+#[test]
+fn link_key() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    // the sender isn't linked yet
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::LinkedActor,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::SenderNotLinked),
+    );
+    // the unlink signed for another actor
+    link_key_utils(
+        &id_program,
+        USER,
+        subject,
+        Signature::Sr25519(
+            subject_pair
+                .sign(&key_link_signing_bytes(&id_program, subject, USER + 1, 1))
+                .0,
+        ),
+        1,
+        Some(IdentityError::InvalidSignature),
+    );
+
+    let signature = Signature::Sr25519(
+        subject_pair
+            .sign(&key_link_signing_bytes(&id_program, subject, USER, 1))
+            .0,
+    );
+    link_key_utils(&id_program, USER, subject, signature, 1, None);
+    // replay the same message
+    link_key_utils(
+        &id_program,
+        USER,
+        subject,
+        signature,
+        1,
+        Some(IdentityError::KeyAlreadyLinked),
+    );
+    check_key_actors_state_utils(&id_program, subject, vec![USER]);
+    check_actor_keys_state_utils(&id_program, USER, vec![subject]);
+    check_nonce_state_utils(&id_program, subject, 2);
+
+    // another sender
+    validation_claim_utils(
+        &id_program,
+        USER + 1,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::LinkedActor,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::SenderNotLinked),
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::LinkedActor,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Suspended);
+
+    unlink_key_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        Some(IdentityError::KeyNotLinked),
+    );
+    unlink_key_utils(&id_program, USER, subject, None);
+    unlink_key_utils(
+        &id_program,
+        USER,
+        subject,
+        Some(IdentityError::KeyNotLinked),
+    );
+    check_key_actors_state_utils(&id_program, subject, vec![]);
+    check_actor_keys_state_utils(&id_program, USER, vec![]);
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::LinkedActor,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Active,
+        Some(IdentityError::SenderNotLinked),
+    );
+
+    // the key holder unlinks a compromised actor
+    link_key_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        Signature::Sr25519(
+            subject_pair
+                .sign(&key_link_signing_bytes(&id_program, subject, USER + 1, 2))
+                .0,
+        ),
+        2,
+        None,
+    );
+    let unlink_signature = |actor, nonce| {
+        Signature::Sr25519(
+            subject_pair
+                .sign(&key_unlink_signing_bytes(
+                    &id_program,
+                    subject,
+                    actor,
+                    nonce,
+                ))
+                .0,
+        )
+    };
+    // the unlink signed for another actor
+    unlink_actor_utils(
+        &id_program,
+        USER + 2,
+        subject,
+        USER + 1,
+        unlink_signature(USER, 3),
+        3,
+        Some(IdentityError::InvalidSignature),
+    );
+    unlink_actor_utils(
+        &id_program,
+        USER + 2,
+        subject,
+        USER,
+        unlink_signature(USER, 3),
+        3,
+        Some(IdentityError::KeyNotLinked),
+    );
+    unlink_actor_utils(
+        &id_program,
+        USER + 2,
+        subject,
+        USER + 1,
+        unlink_signature(USER + 1, 3),
+        3,
+        None,
+    );
+    check_key_actors_state_utils(&id_program, subject, vec![]);
+    check_actor_keys_state_utils(&id_program, USER + 1, vec![]);
+    check_nonce_state_utils(&id_program, subject, 4);
+}
+
+#[test]
+fn revoke_claim() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+    check_revocation_state_utils(&id_program, subject, PIECE_ID, None);
+
+    // the subject can't revoke the claim
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        subject,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        PIECE_ID,
+                        RevocationReason::KeyCompromise,
+                        0,
+                    ))
+                    .0,
+            ),
+            nonce: 0,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        RevocationReason::KeyCompromise,
+        Some(IdentityError::NotIssuer),
+    );
+    // revoke wrong PIECE_ID
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID + 1,
+                        RevocationReason::KeyCompromise,
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID + 1,
+        ClaimStatus::Active,
+        RevocationReason::KeyCompromise,
+        Some(IdentityError::ClaimNotFound),
+    );
+    // the signature of another reason
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID,
+                        RevocationReason::Superseded,
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        RevocationReason::KeyCompromise,
+        Some(IdentityError::InvalidSignature),
+    );
+    check_revocation_state_utils(&id_program, subject, PIECE_ID, None);
+
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID,
+                        RevocationReason::KeyCompromise,
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        RevocationReason::KeyCompromise,
+        None,
+    );
+    check_revocation_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        Some(Revocation {
+            reason: RevocationReason::KeyCompromise,
+            timestamp: sys.block_timestamp(),
+        }),
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Revoked);
+
+    // a revoked claim can't be revoked again
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID,
+                        RevocationReason::Superseded,
+                        2,
+                    ))
+                    .0,
+            ),
+            nonce: 2,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        RevocationReason::Superseded,
+        Some(IdentityError::ClaimRevoked),
+    );
+    // nor re-validated
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Active,
+        Some(IdentityError::ClaimRevoked),
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Revoked);
+}
+
+#[test]
+fn claim_lifecycle() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Suspended,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+
+    // a claim can't be issued suspended
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidInitialStatus),
+    );
+
+    let claim_data = ClaimData {
+        status: ClaimStatus::Pending,
+        ..claim_data
+    };
+    let claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Pending);
+
+    // the subject can't activate the claim
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            0,
+                        ))
+                        .0,
+                ),
+                nonce: 0,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Pending,
+        ClaimStatus::Active,
+        Some(IdentityError::TransitionNotPermitted),
+    );
+    // a pending claim can't be suspended
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Pending,
+        ClaimStatus::Suspended,
+        Some(IdentityError::InvalidTransition),
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: issuer,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    issuer_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            issuer,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            1,
+                        ))
+                        .0,
+                ),
+                nonce: 1,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Pending,
+        ClaimStatus::Active,
+        None,
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Active);
+
+    // only the admin can act as the admin
+    validation_claim_utils(
+        &id_program,
+        USER + 1,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        Some(IdentityError::NotAdmin),
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
+    );
+    // revocation is only performed with IdentityAction::RevokeClaim
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Suspended,
+        ClaimStatus::Revoked,
+        Some(IdentityError::InvalidTransition),
+    );
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Suspended,
+        ClaimStatus::Expired,
+        None,
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Expired);
+
+    // an expired claim can't be reactivated
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Expired,
+        ClaimStatus::Active,
+        Some(IdentityError::InvalidTransition),
+    );
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Expired);
+}
+
+#[test]
+fn validity_period() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let now = sys.block_timestamp();
+    let issue = |valid_from, valid_until, nonce, piece_id, error| {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([city])),
+            schema_id: SCHEMA_ID,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from,
+            valid_until,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer,
+            issuer_signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        issuer,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim, nonce, piece_id, error);
+    };
+
+    // already expired
+    issue(
+        None,
+        Some(now),
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidValidityPeriod),
+    );
+    // empty validity period
+    issue(
+        Some(now + 60_000),
+        Some(now + 60_000),
+        0,
+        PIECE_ID,
+        Some(IdentityError::InvalidValidityPeriod),
+    );
+
+    issue(Some(now + 10_000), Some(now + 60_000), 0, PIECE_ID, None);
+    issue(None, Some(now + 600_000), 1, PIECE_ID + 1, None);
+    issue(None, None, 2, PIECE_ID + 2, None);
+
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Pending);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, false, false);
+    check_valid_state_utils(&id_program, subject, PIECE_ID + 2, ClaimStatus::Active);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID + 2, city, false, true);
+    check_expiring_claims_state_utils(
+        &id_program,
+        subject,
+        100_000,
+        vec![(PIECE_ID, now + 60_000)],
+    );
+    check_expiring_claims_state_utils(
+        &id_program,
+        subject,
+        1_000_000,
+        vec![(PIECE_ID, now + 60_000), (PIECE_ID + 1, now + 600_000)],
+    );
+
+    sys.spend_blocks(20);
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Active);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, false, true);
+
+    sys.spend_blocks(50);
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Expired);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, false, false);
+    check_valid_state_utils(&id_program, subject, PIECE_ID + 1, ClaimStatus::Active);
+    check_expiring_claims_state_utils(&id_program, subject, 0, vec![(PIECE_ID, now + 60_000)]);
+}
+
+#[test]
+fn issuance_date_in_future() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let date = sys.block_timestamp() + MAX_ISSUANCE_DATE_DRIFT;
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: date + 60_000,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        claim,
+        0,
+        PIECE_ID,
+        Some(IdentityError::IssuanceDateInFuture),
+    );
+
+    let claim_data = ClaimData {
+        issuance_date: date,
+        ..claim_data
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+    check_date_state_utils(&id_program, subject, PIECE_ID, issuance_date(&sys, date));
+}
+
+#[test]
+fn issuer_registry() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+
+    let metadata = IssuerMetadata {
+        name: "KYC provider".into(),
+        url: "https://kyc.example".into(),
+        policy_hash: [1; 32],
+        claim_types: vec![SCHEMA_ID],
+    };
+    // the signature of other metadata
+    register_issuer_utils(
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&issuer_registration_signing_bytes(
+                        &id_program,
+                        issuer,
+                        &IssuerMetadata {
+                            name: "Government".into(),
+                            ..metadata.clone()
+                        },
+                        0,
+                    ))
+                    .0,
+            ),
+            nonce: 0,
+        },
+        metadata.clone(),
+        Some(IdentityError::InvalidSignature),
+    );
+    register_issuer_utils(
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&issuer_registration_signing_bytes(
+                        &id_program,
+                        issuer,
+                        &metadata,
+                        0,
+                    ))
+                    .0,
+            ),
+            nonce: 0,
+        },
+        metadata.clone(),
+        None,
+    );
+    check_issuer_state_utils(
+        &id_program,
+        issuer,
+        Some(IssuerInfo {
+            metadata: metadata.clone(),
+            trusted: false,
+        }),
+    );
+    check_issuer_state_utils(&id_program, subject, None);
+
+    // only the admin can trust issuers
+    set_issuer_trust_utils(
+        &id_program,
+        USER + 1,
+        issuer,
+        true,
+        Some(IdentityError::NotAdmin),
+    );
+    set_issuer_trust_utils(
+        &id_program,
+        USER,
+        subject,
+        true,
+        Some(IdentityError::IssuerNotRegistered),
+    );
+    set_issuer_trust_utils(&id_program, USER, issuer, true, None);
+    check_issuer_state_utils(
+        &id_program,
+        issuer,
+        Some(IssuerInfo {
+            metadata: metadata.clone(),
+            trusted: true,
+        }),
+    );
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([city])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let trusted_claim = Claim {
+        issuer,
+        issuer_signature: Signature::Sr25519(
+            issuer_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    1,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data.clone(),
+    };
+    issue_claim_utils(&id_program, USER, trusted_claim.clone(), 1, PIECE_ID, None);
+    let self_issued_claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(
+        &id_program,
+        USER,
+        self_issued_claim.clone(),
+        0,
+        PIECE_ID + 1,
+        None,
+    );
+
+    check_user_claims_state_utils(
+        &id_program,
+        subject,
+        false,
+        vec![
+            (PIECE_ID, trusted_claim.clone()),
+            (PIECE_ID + 1, self_issued_claim),
+        ],
+    );
+    check_user_claims_state_utils(&id_program, subject, true, vec![(PIECE_ID, trusted_claim)]);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, true, true);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID + 1, city, false, true);
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID + 1, city, true, false);
+
+    // updated metadata isn't trusted
+    let metadata = IssuerMetadata {
+        url: "https://kyc.example/v2".into(),
+        ..metadata
+    };
+    register_issuer_utils(
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&issuer_registration_signing_bytes(
+                        &id_program,
+                        issuer,
+                        &metadata,
+                        2,
+                    ))
+                    .0,
+            ),
+            nonce: 2,
+        },
+        metadata.clone(),
+        None,
+    );
+    check_issuer_state_utils(
+        &id_program,
+        issuer,
+        Some(IssuerInfo {
+            metadata,
+            trusted: false,
+        }),
+    );
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, city, true, false);
+}
+
+#[test]
+fn accreditation_chain() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let provider_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let provider = PublicKey::Sr25519(provider_pair.public().0);
+    let root_pair = Sr25519Pair::from_seed(&hex!(
+        "9A61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let root = PublicKey::Sr25519(root_pair.public().0);
+
+    for (schema_id, name) in ["accreditation", "KYC", "residence"]
+        .into_iter()
+        .enumerate()
+    {
+        register_schema_utils(
+            &id_program,
+            USER,
+            name,
+            &[],
+            HashAlgorithm::Sha256,
+            schema_id as SchemaId,
+            None,
+        );
+    }
+    let (accreditation_schema, kyc_schema, residence_schema) = (0, 1, 2);
+
+    let issue = |issuer_pair: &Sr25519Pair,
+                 subject,
+                 schema_id,
+                 accredited_types: Vec<SchemaId>,
+                 nonce,
+                 piece_id| {
+        let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(vec![]),
+            schema_id,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types,
+        };
+        let claim = Claim {
+            issuer,
+            issuer_signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        issuer,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim, nonce, piece_id, None);
+    };
+
+    // the root accredits the provider to issue KYC claims
+    issue(
+        &root_pair,
+        provider,
+        accreditation_schema,
+        vec![kyc_schema],
+        0,
+        PIECE_ID,
+    );
+    issue(&provider_pair, subject, kyc_schema, vec![], 0, PIECE_ID + 1);
+    issue(
+        &provider_pair,
+        subject,
+        residence_schema,
+        vec![],
+        1,
+        PIECE_ID + 2,
+    );
+
+    check_accreditation_chain_state_utils(
+        &id_program,
+        provider,
+        PIECE_ID,
+        root,
+        0,
+        Some(vec![root]),
+    );
+    check_accreditation_chain_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID + 1,
+        root,
+        1,
+        Some(vec![provider, root]),
+    );
+    // the chain is too long
+    check_accreditation_chain_state_utils(&id_program, subject, PIECE_ID + 1, root, 0, None);
+    // the provider isn't accredited to issue claims of this type
+    check_accreditation_chain_state_utils(&id_program, subject, PIECE_ID + 2, root, 5, None);
+    // another root
+    check_accreditation_chain_state_utils(&id_program, subject, PIECE_ID + 1, subject, 5, None);
+    // the depth is capped by the number of claim holders
+    check_accreditation_chain_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID + 1,
+        root,
+        u32::MAX,
+        Some(vec![provider, root]),
+    );
+    check_accreditation_chain_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID + 1,
+        subject,
+        u32::MAX,
+        None,
+    );
+
+    // suspended accreditations are ignored
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        provider,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
+    );
+    check_accreditation_chain_state_utils(&id_program, subject, PIECE_ID + 1, root, 1, None);
+}
+
+#[test]
+fn claim_schemas() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    // attribute names must be unique
+    register_schema_utils(
+        &id_program,
+        USER,
+        "address",
+        &["city", "city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        Some(IdentityError::InvalidSchema),
+    );
+    register_schema_utils(
+        &id_program,
+        USER,
+        "address",
+        &["city", "street"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    register_schema_utils(
+        &id_program,
+        USER + 1,
+        "residence",
+        &["city"],
+        HashAlgorithm::Blake2b256,
+        SCHEMA_ID + 1,
+        None,
+    );
+    let address_schema = Schema {
+        id: SCHEMA_ID,
+        name: "address".into(),
+        attributes: vec!["city".into(), "street".into()],
+        hash_algorithm: HashAlgorithm::Sha256,
+    };
+    let residence_schema = Schema {
+        id: SCHEMA_ID + 1,
+        name: "residence".into(),
+        attributes: vec!["city".into()],
+        hash_algorithm: HashAlgorithm::Blake2b256,
+    };
+    check_schema_state_utils(&id_program, SCHEMA_ID, Some(address_schema.clone()));
+    check_schema_state_utils(&id_program, SCHEMA_ID + 2, None);
+    check_schemas_state_utils(&id_program, vec![address_schema, residence_schema]);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let mut hasher = Sha256::new();
+    hasher.update(b"Nikolskaya");
+    let street = hasher.finalize().as_slice().try_into().expect("Wrong size");
+
+    let issue = |hashed_info, schema_id, nonce, piece_id, error| {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(hashed_info),
+            schema_id,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer: subject,
+            issuer_signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim.clone(), nonce, piece_id, error);
+        claim
+    };
+
+    // the street hash is missing
+    issue(
+        vec![city],
+        SCHEMA_ID,
+        0,
+        PIECE_ID,
+        Some(IdentityError::SchemaMismatch),
+    );
+    issue(
+        vec![city],
+        SCHEMA_ID + 2,
+        0,
+        PIECE_ID,
+        Some(IdentityError::SchemaNotFound),
+    );
+    let address_claim = issue(vec![city, street], SCHEMA_ID, 0, PIECE_ID, None);
+    let residence_claim = issue(vec![city], SCHEMA_ID + 1, 1, PIECE_ID + 1, None);
+
+    check_claims_by_schema_state_utils(&id_program, SCHEMA_ID, vec![(PIECE_ID, address_claim)]);
+    check_claims_by_schema_state_utils(
+        &id_program,
+        SCHEMA_ID + 1,
+        vec![(PIECE_ID + 1, residence_claim)],
+    );
+    check_claims_by_schema_state_utils(&id_program, SCHEMA_ID + 2, vec![]);
+}
+
+#[test]
+fn salted_commitments() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    register_schema_utils(
+        &id_program,
+        USER,
+        "birth",
+        &["date"],
+        HashAlgorithm::Blake2b256,
+        SCHEMA_ID + 1,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    // the salts are only known to the subject
+    let city_salt = [7; 32];
+    let date_salt = [9; 32];
+    let city = HashAlgorithm::Sha256.commitment(&city_salt, b"Amsterdam");
+    let date = HashAlgorithm::Blake2b256.commitment(&date_salt, b"1990-01-01");
+    let mut hasher = Sha256::new();
+    hasher.update(city_salt);
+    hasher.update(b"Amsterdam");
+    assert_eq!(city, <[u8; 32]>::from(hasher.finalize()));
+
+    for (piece_id, (schema_id, commitment)) in [(SCHEMA_ID, city), (SCHEMA_ID + 1, date)]
+        .into_iter()
+        .enumerate()
+    {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([commitment])),
+            schema_id,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer: subject,
+            issuer_signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        &claim_data,
+                        piece_id as u64,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(
+            &id_program,
+            USER,
+            claim,
+            piece_id as u64,
+            piece_id as PieceId,
+            None,
+        );
+    }
+
+    let presentation = AttributePresentation {
+        attribute: "city".into(),
+        value: b"Amsterdam".to_vec(),
+        salt: city_salt,
+    };
+    check_presentation_state_utils(&id_program, subject, PIECE_ID, presentation.clone(), true);
+    // the bare hash doesn't match the commitment
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+    let bare_hash = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "city", bare_hash, false);
+    check_presentation_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        AttributePresentation {
+            salt: [0; 32],
+            ..presentation.clone()
+        },
+        false,
+    );
+    check_presentation_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        AttributePresentation {
+            value: b"Rotterdam".to_vec(),
+            ..presentation
+        },
+        false,
+    );
+
+    let presentation = AttributePresentation {
+        attribute: "date".into(),
+        value: b"1990-01-01".to_vec(),
+        salt: date_salt,
+    };
+    check_presentation_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID + 1,
+        presentation.clone(),
+        true,
+    );
+    // the commitment of another schema's hash algorithm
+    check_presentation_state_utils(&id_program, subject, PIECE_ID, presentation, false);
+}
+
+#[test]
+fn merkle_claims() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "passport",
+        &["name", "birth", "city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let leaves: Vec<[u8; 32]> = [&b"Alice"[..], b"1990-01-01", b"Amsterdam"]
+        .into_iter()
+        .map(|value| Sha256::digest(value).into())
+        .collect();
+    let root = merkle_root(&leaves);
+    for (index, leaf) in leaves.iter().enumerate() {
+        let path = merkle_path(&leaves, index);
+        assert_eq!(path.len(), 2);
+        assert!(verify_merkle_path(&root, 3, index as u32, leaf, &path));
+    }
+    assert_eq!(merkle_root(&[]), [0; 32]);
+    assert!(verify_merkle_path(
+        &merkle_root(&leaves[..1]),
+        1,
+        0,
+        &leaves[0],
+        &[]
+    ));
+
+    let issue = |leaf_count, nonce, piece_id, error| {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::MerkleRoot { root, leaf_count },
+            schema_id: SCHEMA_ID,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer: subject,
+            issuer_signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim, nonce, piece_id, error);
+    };
+    issue(2, 0, PIECE_ID, Some(IdentityError::SchemaMismatch));
+    issue(3, 0, PIECE_ID, None);
+
+    let city_path = merkle_path(&leaves, 2);
+    check_merkle_attribute_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        "city",
+        leaves[2],
+        city_path.clone(),
+        true,
+    );
+    check_merkle_attribute_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID,
+        "city",
+        leaves[2],
+        city_path.clone(),
+        true,
+    );
+    // the leaf of another attribute
+    check_merkle_attribute_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        "name",
+        leaves[2],
+        city_path.clone(),
+        false,
+    );
+    check_merkle_attribute_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID,
+        "name",
+        leaves[2],
+        city_path.clone(),
+        false,
+    );
+    check_merkle_attribute_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        "city",
+        leaves[2],
+        city_path[..1].to_vec(),
+        false,
+    );
+    check_merkle_attribute_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        "street",
+        leaves[2],
+        city_path.clone(),
+        false,
+    );
+    // plain hashes don't match a Merkle root claim
+    check_claim_hash_state_utils(&id_program, subject, PIECE_ID, leaves[2], false, false);
+    check_claim_attribute_state_utils(&id_program, subject, PIECE_ID, "city", leaves[2], false);
+
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Expired,
+        None,
+    );
+    check_merkle_attribute_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        "city",
+        leaves[2],
+        city_path,
+        false,
+    );
+}
+
+#[test]
+fn threshold_proofs() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "age",
+        &["age"],
+        HashAlgorithm::Blake2b256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    // the seed is only known to the issuer and the subject
+    let seed = [5; 32];
+    let age = 25;
+    let commitment = HashAlgorithm::Blake2b256.hash_chain(&seed, age);
+    assert_eq!(HashAlgorithm::Blake2b256.hash_chain(&seed, 0), seed);
+
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([commitment])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    let over_18 = ThresholdProof {
+        attribute: "age".into(),
+        threshold: 18,
+        hash: HashAlgorithm::Blake2b256.hash_chain(&seed, age - 18),
+    };
+    check_threshold_state_utils(&id_program, subject, PIECE_ID, over_18.clone(), true);
+    // the proof doesn't reach a higher threshold
+    check_threshold_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        ThresholdProof {
+            threshold: 21,
+            ..over_18.clone()
+        },
+        false,
+    );
+    check_threshold_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        ThresholdProof {
+            threshold: 30,
+            hash: seed,
+            ..over_18.clone()
+        },
+        false,
+    );
+    check_threshold_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        ThresholdProof {
+            attribute: "height".into(),
+            ..over_18.clone()
+        },
+        false,
+    );
+    check_threshold_state_utils(&id_program, subject, PIECE_ID + 1, over_18.clone(), false);
+    // too many hash rounds are rejected before hashing
+    check_threshold_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        ThresholdProof {
+            threshold: MAX_THRESHOLD + 1,
+            hash: seed,
+            ..over_18.clone()
+        },
+        false,
+    );
+
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Expired,
+        None,
+    );
+    check_threshold_state_utils(&id_program, subject, PIECE_ID, over_18, false);
+}
+
+#[test]
+fn withdraw_verification() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    let verifier_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
+    let other_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F61"
+    ));
+    let other = PublicKey::Sr25519(other_pair.public().0);
+    for (pair, key) in [(&verifier_pair, verifier), (&other_pair, other)] {
+        verify_claim_utils(
+            &id_program,
+            USER,
+            key,
+            Signature::Sr25519(
+                pair.sign(&verification_signing_bytes(
+                    &id_program,
+                    key,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+            ),
+            subject,
+            PIECE_ID,
+            STATEMENT,
+            0,
+            None,
+        );
+    }
+    check_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![verifier, other]);
+
+    let reason = WithdrawalReason::ClaimFalse;
+    let proof = |pair: &Sr25519Pair, key, reason, nonce| KeyProof::Signature {
+        signature: Signature::Sr25519(
+            pair.sign(&withdrawal_signing_bytes(
+                &id_program,
+                key,
+                subject,
+                PIECE_ID,
+                reason,
+                nonce,
+            ))
+            .0,
+        ),
+        nonce,
+    };
+    // the subject hasn't verified the claim
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        subject,
+        proof(&subject_pair, subject, reason, 1),
+        subject,
+        PIECE_ID,
+        reason,
+        Some(IdentityError::NotVerifier),
+    );
+    // the signature of another reason
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifier,
+        proof(&verifier_pair, verifier, WithdrawalReason::Unspecified, 1),
+        subject,
+        PIECE_ID,
+        reason,
+        Some(IdentityError::InvalidSignature),
+    );
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifier,
+        KeyProof::LinkedActor,
+        subject,
+        PIECE_ID,
+        reason,
+        Some(IdentityError::SenderNotLinked),
+    );
+    check_withdrawal_state_utils(&id_program, subject, PIECE_ID, verifier, None);
+
+    sys.spend_blocks(1);
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifier,
+        proof(&verifier_pair, verifier, reason, 1),
+        subject,
+        PIECE_ID,
+        reason,
+        None,
+    );
+    check_withdrawal_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        verifier,
+        Some(Withdrawal {
+            reason,
+            timestamp: sys.block_timestamp(),
+        }),
+    );
+    check_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![other]);
+    check_all_verifiers_state_utils(&id_program, subject, PIECE_ID, vec![verifier, other]);
+
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifier,
+        proof(&verifier_pair, verifier, reason, 2),
+        subject,
+        PIECE_ID,
+        reason,
+        Some(IdentityError::VerificationWithdrawn),
+    );
+    verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    2,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        2,
+        Some(IdentityError::VerificationWithdrawn),
+    );
+    check_withdrawal_state_utils(&id_program, subject, PIECE_ID, other, None);
+}
+
+#[test]
+fn verification_policy() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    let verifier_pairs: Vec<Sr25519Pair> = (0..4)
+        .map(|i| {
+            let mut seed = hex!("9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60");
+            seed[31] = i;
+            Sr25519Pair::from_seed(&seed)
+        })
+        .collect();
+    let verifiers: Vec<PublicKey> = verifier_pairs
+        .iter()
+        .map(|pair| PublicKey::Sr25519(pair.public().0))
+        .collect();
+    let verify = |i: usize| {
+        verify_claim_utils(
+            &id_program,
+            USER,
+            verifiers[i],
+            Signature::Sr25519(
+                verifier_pairs[i]
+                    .sign(&verification_signing_bytes(
+                        &id_program,
+                        verifiers[i],
+                        subject,
+                        PIECE_ID,
+                        STATEMENT,
+                        0,
+                    ))
+                    .0,
+            ),
+            subject,
+            PIECE_ID,
+            STATEMENT,
+            0,
+            None,
+        )
+    };
+    let proof = |pair: &Sr25519Pair, owner, policy, nonce| KeyProof::Signature {
+        signature: Signature::Sr25519(
+            pair.sign(&verification_policy_signing_bytes(
+                &id_program,
+                owner,
+                subject,
+                PIECE_ID,
+                policy,
+                nonce,
+            ))
+            .0,
+        ),
+        nonce,
+    };
+
+    // 2 of the first 3 verifiers
+    let policy = VerificationPolicy {
+        threshold: 2,
+        verifiers: verifiers[..3].to_vec(),
+    };
+    for (threshold, error) in [
+        (0, IdentityError::InvalidPolicy),
+        (4, IdentityError::InvalidPolicy),
+    ] {
+        let policy = VerificationPolicy {
+            threshold,
+            ..policy.clone()
+        };
+        set_verification_policy_utils(
+            &id_program,
+            USER,
+            subject,
+            proof(&subject_pair, subject, policy.clone(), 1),
+            subject,
+            PIECE_ID,
+            policy,
+            Some(error),
+        );
+    }
+    set_verification_policy_utils(
+        &id_program,
+        USER,
+        verifiers[0],
+        proof(&verifier_pairs[0], verifiers[0], policy.clone(), 0),
+        subject,
+        PIECE_ID,
+        policy.clone(),
+        Some(IdentityError::NotSubjectOrIssuer),
+    );
+    let res = set_verification_policy_utils(
+        &id_program,
+        USER,
+        subject,
+        proof(&subject_pair, subject, policy.clone(), 1),
+        subject,
+        PIECE_ID,
+        policy.clone(),
+        None,
+    );
+    check_quorum_event(&res, subject, PIECE_ID, false);
+    check_verification_policy_state_utils(&id_program, subject, PIECE_ID, Some(policy));
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, false);
+
+    // the verifier isn't listed in the policy
+    check_quorum_event(&verify(3), subject, PIECE_ID, false);
+    check_quorum_event(&verify(0), subject, PIECE_ID, false);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, false);
+    check_quorum_event(&verify(1), subject, PIECE_ID, true);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, true);
+
+    withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifiers[0],
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                verifier_pairs[0]
+                    .sign(&withdrawal_signing_bytes(
+                        &id_program,
+                        verifiers[0],
+                        subject,
+                        PIECE_ID,
+                        WithdrawalReason::ClaimFalse,
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID,
+        WithdrawalReason::ClaimFalse,
+        None,
+    );
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, false);
+    // the quorum is reported only once
+    check_quorum_event(&verify(2), subject, PIECE_ID, false);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, true);
+
+    // 3 of any verifiers
+    let policy = VerificationPolicy {
+        threshold: 3,
+        verifiers: vec![],
+    };
+    let res = set_verification_policy_utils(
+        &id_program,
+        USER,
+        subject,
+        proof(&subject_pair, subject, policy.clone(), 2),
+        subject,
+        PIECE_ID,
+        policy.clone(),
+        None,
+    );
+    check_quorum_event(&res, subject, PIECE_ID, true);
+    check_verification_policy_state_utils(&id_program, subject, PIECE_ID, Some(policy.clone()));
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, true);
+    check_verification_policy_state_utils(&id_program, subject, PIECE_ID + 1, None);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID + 1, false);
+
+    // a revoked claim isn't sufficiently verified anymore
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        subject,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        PIECE_ID,
+                        RevocationReason::Misissued,
+                        3,
+                    ))
+                    .0,
+            ),
+            nonce: 3,
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        RevocationReason::Misissued,
+        None,
+    );
+    check_verification_policy_state_utils(&id_program, subject, PIECE_ID, Some(policy));
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, false);
+}
+
+#[test]
+fn quorum_on_activation() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let verifier_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([result])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Pending,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    let policy = VerificationPolicy {
+        threshold: 1,
+        verifiers: vec![],
+    };
+    set_verification_policy_utils(
+        &id_program,
+        USER,
+        subject,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&verification_policy_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        PIECE_ID,
+                        policy.clone(),
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID,
+        policy,
+        None,
+    );
+
+    // a pending claim doesn't reach the quorum
+    let res = verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        None,
+    );
+    check_quorum_event(&res, subject, PIECE_ID, false);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, false);
+
+    // the issuer activates the already verified claim
+    let res = validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Key {
+            key: subject,
+            proof: KeyProof::Signature {
+                signature: Signature::Sr25519(
+                    subject_pair
+                        .sign(&status_change_signing_bytes(
+                            &id_program,
+                            subject,
+                            subject,
+                            PIECE_ID,
+                            ClaimStatus::Active,
+                            2,
+                        ))
+                        .0,
+                ),
+                nonce: 2,
+            },
+        },
+        subject,
+        PIECE_ID,
+        ClaimStatus::Pending,
+        ClaimStatus::Active,
+        None,
+    );
+    check_quorum_event(&res, subject, PIECE_ID, true);
+    check_sufficiently_verified_state_utils(&id_program, subject, PIECE_ID, true);
+}
+
+#[test]
+fn relying_party_policies() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    register_schema_utils(
+        &id_program,
+        USER,
+        "birth",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID + 1,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+    let verifier_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let issue = |pair: &Sr25519Pair, schema_id, nonce, piece_id| {
+        let issuer = PublicKey::Sr25519(pair.public().0);
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([result])),
+            schema_id,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer,
+            issuer_signature: Signature::Sr25519(
+                pair.sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    issuer,
+                    &claim_data,
+                    nonce,
+                ))
+                .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim, nonce, piece_id, None);
+    };
+    issue(&issuer_pair, SCHEMA_ID, 0, PIECE_ID);
+    // self-issued
+    issue(&subject_pair, SCHEMA_ID, 0, PIECE_ID + 1);
+    // another schema
+    issue(&issuer_pair, SCHEMA_ID + 1, 1, PIECE_ID + 2);
+    issue(&issuer_pair, SCHEMA_ID, 2, PIECE_ID + 3);
+
+    register_policy_utils(
+        &id_program,
+        USER,
+        "resident",
+        SCHEMA_ID + 2,
+        vec![],
+        0,
+        None,
+        0,
+        Some(IdentityError::SchemaNotFound),
+    );
+    register_policy_utils(
+        &id_program,
+        USER,
+        "verified resident",
+        SCHEMA_ID,
+        vec![issuer],
+        1,
+        Some(5000),
+        0,
+        None,
+    );
+    register_policy_utils(
+        &id_program,
+        USER + 1,
+        "resident",
+        SCHEMA_ID,
+        vec![],
+        0,
+        None,
+        1,
+        None,
+    );
+    check_policy_state_utils(
+        &id_program,
+        0,
+        Some(RelyingPartyPolicy {
+            id: 0,
+            owner: USER.into(),
+            name: "verified resident".into(),
+            schema_id: SCHEMA_ID,
+            issuers: vec![issuer],
+            min_verifiers: 1,
+            max_age: Some(5000),
+        }),
+    );
+    check_policy_state_utils(&id_program, 2, None);
+
+    // there are no verifications yet
+    check_satisfies_policy_state_utils(&id_program, subject, 0, vec![]);
+    for (nonce, piece_id) in [PIECE_ID, PIECE_ID + 1, PIECE_ID + 2, PIECE_ID + 3]
+        .into_iter()
+        .enumerate()
+    {
+        verify_claim_utils(
+            &id_program,
+            USER,
+            verifier,
+            Signature::Sr25519(
+                verifier_pair
+                    .sign(&verification_signing_bytes(
+                        &id_program,
+                        verifier,
+                        subject,
+                        piece_id,
+                        STATEMENT,
+                        nonce as u64,
+                    ))
+                    .0,
+            ),
+            subject,
+            piece_id,
+            STATEMENT,
+            nonce as u64,
+            None,
+        );
+    }
+    check_satisfies_policy_state_utils(&id_program, subject, 0, vec![PIECE_ID, PIECE_ID + 3]);
+
+    revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID + 3,
+                        RevocationReason::Superseded,
+                        3,
+                    ))
+                    .0,
+            ),
+            nonce: 3,
+        },
+        subject,
+        PIECE_ID + 3,
+        ClaimStatus::Active,
+        RevocationReason::Superseded,
+        None,
+    );
+    check_satisfies_policy_state_utils(&id_program, subject, 0, vec![PIECE_ID]);
+    satisfies_policy_utils(&id_program, USER, subject, 0, vec![PIECE_ID], None);
+    check_satisfies_policy_state_utils(&id_program, subject, 1, vec![PIECE_ID, PIECE_ID + 1]);
+
+    // the claims are too old for the first policy
+    sys.spend_blocks(6);
+    check_satisfies_policy_state_utils(&id_program, subject, 0, vec![]);
+    satisfies_policy_utils(&id_program, USER, subject, 0, vec![], None);
+    satisfies_policy_utils(
+        &id_program,
+        USER,
+        subject,
+        1,
+        vec![PIECE_ID, PIECE_ID + 1],
+        None,
+    );
+    check_satisfies_policy_state_utils(&id_program, verifier, 1, vec![]);
+
+    check_satisfies_policy_state_utils(&id_program, subject, 2, vec![]);
+    satisfies_policy_utils(
+        &id_program,
+        USER,
+        subject,
+        2,
+        vec![],
+        Some(IdentityError::PolicyNotFound),
+    );
+}
+
+#[test]
+fn query_actions() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let city = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let claim_data = ClaimData {
+        hashed_info: HashedInfo::List(Vec::from([city])),
+        schema_id: SCHEMA_ID,
+        issuance_date: DATE,
+        status: ClaimStatus::Active,
+        valid_from: None,
+        valid_until: None,
+        accredited_types: vec![],
+    };
+    let claim = Claim {
+        issuer: subject,
+        issuer_signature: Signature::Sr25519(
+            subject_pair
+                .sign(&claim_signing_bytes(
+                    &id_program,
+                    subject,
+                    subject,
+                    &claim_data,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        verifiers: vec![],
+        issuance: issuance_date(&sys, claim_data.issuance_date),
+        data: claim_data,
+    };
+    issue_claim_utils(&id_program, USER, claim, 0, PIECE_ID, None);
+
+    // the calling program is a user in the tests
+    query_claim_status_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        PIECE_ID,
+        Some(ClaimStatus::Active),
+    );
+    query_claim_status_utils(&id_program, USER + 1, subject, PIECE_ID + 1, None);
+    query_check_claim_utils(&id_program, USER + 1, subject, PIECE_ID, city, false, true);
+    // the self-issuer isn't trusted
+    query_check_claim_utils(&id_program, USER + 1, subject, PIECE_ID, city, true, false);
+    query_check_claim_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        PIECE_ID,
+        [0; 32],
+        false,
+        false,
+    );
+    query_check_claim_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        PIECE_ID + 1,
+        city,
+        false,
+        false,
+    );
+
+    validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Expired,
+        None,
+    );
+    query_claim_status_utils(
+        &id_program,
+        USER + 1,
+        subject,
+        PIECE_ID,
+        Some(ClaimStatus::Expired),
+    );
+    query_check_claim_utils(&id_program, USER + 1, subject, PIECE_ID, city, false, false);
+}
+
+#[test]
+fn subscriptions() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    let issuer_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B2EBC4197073EF857A385EB42990CB647497353884B9703BAC031CAE7F60"
+    ));
+    let issuer = PublicKey::Sr25519(issuer_pair.public().0);
+    let verifier_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5D60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let verifier = PublicKey::Sr25519(verifier_pair.public().0);
+    let (subject_subscriber, claim_subscriber, issuer_subscriber, limit_subscriber) =
+        (USER + 1, USER + 2, USER + 3, USER + 4);
+
+    subscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        2,
+        2,
+        None,
+    );
+    subscribe_utils(
+        &id_program,
+        claim_subscriber,
+        SubscriptionTarget::Claim(subject, PIECE_ID + 1),
+        5,
+        5,
+        None,
+    );
+    subscribe_utils(
+        &id_program,
+        issuer_subscriber,
+        SubscriptionTarget::Issuer(issuer),
+        MAX_RESERVED_NOTIFICATIONS + 1,
+        0,
+        Some(IdentityError::SubscriptionLimitExceeded),
+    );
+    subscribe_utils(
+        &id_program,
+        issuer_subscriber,
+        SubscriptionTarget::Issuer(issuer),
+        1,
+        1,
+        None,
+    );
+    subscribe_utils(
+        &id_program,
+        limit_subscriber,
+        SubscriptionTarget::Issuer(issuer),
+        0,
+        0,
+        Some(IdentityError::NoNotificationsReserved),
+    );
+    for piece_id in 0..MAX_SUBSCRIPTIONS {
+        subscribe_utils(
+            &id_program,
+            limit_subscriber,
+            SubscriptionTarget::Claim(issuer, piece_id as PieceId),
+            1,
+            piece_id as u32 + 1,
+            None,
+        );
+    }
+    subscribe_utils(
+        &id_program,
+        limit_subscriber,
+        SubscriptionTarget::Subject(issuer),
+        1,
+        0,
+        Some(IdentityError::SubscriptionLimitExceeded),
+    );
+    // subscribing to the same target again doesn't require new notifications
+    subscribe_utils(
+        &id_program,
+        limit_subscriber,
+        SubscriptionTarget::Claim(issuer, 0),
+        0,
+        MAX_SUBSCRIPTIONS as u32,
+        None,
+    );
+    check_subscription_state_utils(
+        &id_program,
+        subject_subscriber,
+        vec![SubscriptionTarget::Subject(subject)],
+        2,
+    );
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let issue = |nonce, piece_id| {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([result])),
+            schema_id: SCHEMA_ID,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until: None,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer,
+            issuer_signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        issuer,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim, nonce, piece_id, None)
+    };
+    let issued = |piece_id| IdentityEvent::ClaimIssued {
+        issuer,
+        subject,
+        piece_id,
+    };
+
+    let res = issue(0, PIECE_ID);
+    check_notification(&res, subject_subscriber, issued(PIECE_ID), true);
+    check_notification(&res, issuer_subscriber, issued(PIECE_ID), true);
+    check_notification(&res, claim_subscriber, issued(PIECE_ID), false);
+
+    let res = validation_claim_utils(
+        &id_program,
+        USER,
+        Authority::Admin,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        ClaimStatus::Suspended,
+        None,
+    );
+    let changed = || IdentityEvent::ClaimValidationChanged {
+        validator: None,
+        subject,
+        piece_id: PIECE_ID,
+        old_status: ClaimStatus::Active,
+        new_status: ClaimStatus::Suspended,
+    };
+    check_notification(&res, subject_subscriber, changed(), true);
+    // the issuer subscriber has no reserved notifications left
+    check_notification(&res, issuer_subscriber, changed(), false);
+    // the exhausted subscribers are unsubscribed
+    check_subscription_state_utils(&id_program, issuer_subscriber, vec![], 0);
+    check_subscription_state_utils(&id_program, subject_subscriber, vec![], 0);
+    unsubscribe_utils(
+        &id_program,
+        issuer_subscriber,
+        SubscriptionTarget::Issuer(issuer),
+        Some(IdentityError::NotSubscribed),
+    );
+
+    // subscribe again
+    subscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        3,
+        3,
+        None,
+    );
+    let res = verify_claim_utils(
+        &id_program,
+        USER,
+        verifier,
+        Signature::Sr25519(
+            verifier_pair
+                .sign(&verification_signing_bytes(
+                    &id_program,
+                    verifier,
+                    subject,
+                    PIECE_ID,
+                    STATEMENT,
+                    0,
+                ))
+                .0,
+        ),
+        subject,
+        PIECE_ID,
+        STATEMENT,
+        0,
+        None,
+    );
+    let verified = IdentityEvent::VerifiedClaim {
+        verifier,
+        subject,
+        piece_id: PIECE_ID,
+    };
+    check_notification(&res, subject_subscriber, verified, true);
+    let res = withdraw_verification_utils(
+        &sys,
+        &id_program,
+        USER,
+        verifier,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                verifier_pair
+                    .sign(&withdrawal_signing_bytes(
+                        &id_program,
+                        verifier,
+                        subject,
+                        PIECE_ID,
+                        WithdrawalReason::ClaimFalse,
+                        1,
+                    ))
+                    .0,
+            ),
+            nonce: 1,
+        },
+        subject,
+        PIECE_ID,
+        WithdrawalReason::ClaimFalse,
+        None,
+    );
+    let withdrawn = IdentityEvent::VerificationWithdrawn {
+        verifier,
+        subject,
+        piece_id: PIECE_ID,
+        reason: WithdrawalReason::ClaimFalse,
+        timestamp: sys.block_timestamp(),
+    };
+    check_notification(&res, subject_subscriber, withdrawn, true);
+
+    unsubscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        None,
+    );
+    unsubscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        Some(IdentityError::NotSubscribed),
+    );
+    check_subscription_state_utils(&id_program, subject_subscriber, vec![], 0);
+
+    let res = issue(1, PIECE_ID + 1);
+    check_notification(&res, claim_subscriber, issued(PIECE_ID + 1), true);
+    check_notification(&res, subject_subscriber, issued(PIECE_ID + 1), false);
+    let res = revoke_claim_utils(
+        &sys,
+        &id_program,
+        USER,
+        issuer,
+        KeyProof::Signature {
+            signature: Signature::Sr25519(
+                issuer_pair
+                    .sign(&revocation_signing_bytes(
+                        &id_program,
+                        issuer,
+                        subject,
+                        PIECE_ID + 1,
+                        RevocationReason::Misissued,
+                        2,
+                    ))
+                    .0,
+            ),
+            nonce: 2,
+        },
+        subject,
+        PIECE_ID + 1,
+        ClaimStatus::Active,
+        RevocationReason::Misissued,
+        None,
+    );
+    let revoked = IdentityEvent::ClaimRevoked {
+        issuer,
+        subject,
+        piece_id: PIECE_ID + 1,
+        old_status: ClaimStatus::Active,
+        reason: RevocationReason::Misissued,
+        timestamp: sys.block_timestamp(),
+    };
+    check_notification(&res, claim_subscriber, revoked, true);
+    check_subscription_state_utils(
+        &id_program,
+        claim_subscriber,
+        vec![SubscriptionTarget::Claim(subject, PIECE_ID + 1)],
+        3,
+    );
+    unsubscribe_utils(
+        &id_program,
+        claim_subscriber,
+        SubscriptionTarget::Claim(subject, PIECE_ID + 1),
+        None,
+    );
+    check_subscription_state_utils(&id_program, claim_subscriber, vec![], 0);
+
+    // expired reservations don't prevent unsubscribing
+    subscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        1,
+        1,
+        None,
+    );
+    sys.spend_blocks(NOTIFICATION_RESERVATION_BLOCKS + 1);
+    unsubscribe_utils(
+        &id_program,
+        subject_subscriber,
+        SubscriptionTarget::Subject(subject),
+        None,
+    );
+    check_subscription_state_utils(&id_program, subject_subscriber, vec![], 0);
+
+    // the runtime holds at most 256 gas reservations of the program
+    for subscriber in USER + 10..USER + 13 {
+        subscribe_utils(
+            &id_program,
+            subscriber,
+            SubscriptionTarget::Subject(subject),
+            MAX_RESERVED_NOTIFICATIONS,
+            MAX_RESERVED_NOTIFICATIONS,
+            None,
+        );
+    }
+    subscribe_utils(
+        &id_program,
+        USER + 13,
+        SubscriptionTarget::Subject(subject),
+        60,
+        60,
+        None,
+    );
+    subscribe_utils(
+        &id_program,
+        USER + 14,
+        SubscriptionTarget::Subject(subject),
+        10,
+        0,
+        Some(IdentityError::GasReservationFailed),
+    );
+    check_subscription_state_utils(&id_program, USER + 14, vec![], 0);
+    // the partially made reservations are returned
+    subscribe_utils(
+        &id_program,
+        USER + 14,
+        SubscriptionTarget::Subject(subject),
+        4,
+        4,
+        None,
+    );
+}
+
+#[test]
+fn target_subscribers_limit() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    let subject = PublicKey::Sr25519([1; 32]);
+    let target = SubscriptionTarget::Subject(subject);
+
+    for subscriber in USER + 1..=USER + MAX_TARGET_SUBSCRIBERS as u64 {
+        subscribe_utils(&id_program, subscriber, target, 1, 1, None);
+    }
+    subscribe_utils(
+        &id_program,
+        USER,
+        target,
+        1,
+        0,
+        Some(IdentityError::SubscriptionLimitExceeded),
+    );
+    // other targets and the existing subscribers aren't affected
+    subscribe_utils(
+        &id_program,
+        USER,
+        SubscriptionTarget::Issuer(subject),
+        1,
+        1,
+        None,
+    );
+    subscribe_utils(&id_program, USER + 1, target, 1, 2, None);
+
+    // an unsubscribed subscriber frees the place
+    unsubscribe_utils(&id_program, USER + 1, target, None);
+    subscribe_utils(&id_program, USER, target, 1, 2, None);
+    check_subscription_state_utils(
+        &id_program,
+        USER,
+        vec![SubscriptionTarget::Issuer(subject), target],
+        2,
+    );
+}
+
+#[test]
+fn auto_expiry() {
+    let sys = System::new();
+    let id_program = init_identity(&sys, USER);
+    register_schema_utils(
+        &id_program,
+        USER,
+        "residence",
+        &["city"],
+        HashAlgorithm::Sha256,
+        SCHEMA_ID,
+        None,
+    );
+    let subject_pair = Sr25519Pair::from_seed(&hex!(
+        "9D61B19DEFFD5A60BA844AF492EC2CC44449C5697B326919703BAC031CAE7F60"
+    ));
+    let subject = PublicKey::Sr25519(subject_pair.public().0);
+    subscribe_utils(
+        &id_program,
+        USER + 1,
+        SubscriptionTarget::Claim(subject, PIECE_ID),
+        2,
+        2,
+        None,
+    );
+
+    let mut hasher = Sha256::new();
+    hasher.update(b"Amsterdam");
+
+    // read hash digest and consume hasher
+    let result = hasher.finalize().as_slice().try_into().expect("Wrong size");
+    let now = sys.block_timestamp();
+    let issue = |valid_until, nonce, piece_id| {
+        let claim_data = ClaimData {
+            hashed_info: HashedInfo::List(Vec::from([result])),
+            schema_id: SCHEMA_ID,
+            issuance_date: DATE,
+            status: ClaimStatus::Active,
+            valid_from: None,
+            valid_until,
+            accredited_types: vec![],
+        };
+        let claim = Claim {
+            issuer: subject,
+            issuer_signature: Signature::Sr25519(
+                subject_pair
+                    .sign(&claim_signing_bytes(
+                        &id_program,
+                        subject,
+                        subject,
+                        &claim_data,
+                        nonce,
+                    ))
+                    .0,
+            ),
+            subject,
+            verifiers: vec![],
+            issuance: issuance_date(&sys, claim_data.issuance_date),
+            data: claim_data,
+        };
+        issue_claim_utils(&id_program, USER, claim.clone(), nonce, piece_id, None);
+        claim
+    };
+    // expires in 7 blocks of 1 s, but the expiry is scheduled in 3 expected blocks
+    let claim = issue(Some(now + 2 * BLOCK_DURATION + 500), 0, PIECE_ID);
+    issue(None, 1, PIECE_ID + 1);
+
+    expire_claim_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        Some(IdentityError::ClaimNotExpired),
+    );
+    expire_claim_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID + 1,
+        ClaimStatus::Active,
+        Some(IdentityError::ClaimNotExpired),
+    );
+    expire_claim_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID + 2,
+        ClaimStatus::Active,
+        Some(IdentityError::ClaimNotFound),
+    );
+
+    let expired = || IdentityEvent::ClaimExpired {
+        subject,
+        piece_id: PIECE_ID,
+        old_status: ClaimStatus::Active,
+    };
+    let results = sys.spend_blocks(2);
+    assert!(results.is_empty());
+
+    // the expiry arrives too early and is rescheduled with the observed block duration
+    let results = sys.spend_blocks(1);
+    assert_eq!(results.len(), 1);
+    assert!(results[0].contains(&(
+        id_program.id(),
+        Err::<IdentityEvent, _>(IdentityError::ClaimNotExpired).encode()
+    )));
+    check_notification(&results[0], 0, expired(), false);
+    check_notification(&results[0], USER + 1, expired(), false);
+    check_claim_state_utils(&id_program, subject, PIECE_ID, claim.clone());
+    let results = sys.spend_blocks(3);
+    assert!(results.is_empty());
+    check_valid_state_utils(&id_program, subject, PIECE_ID, ClaimStatus::Active);
+
+    let results = sys.spend_blocks(1);
+    assert_eq!(results.len(), 1);
+    check_notification(&results[0], 0, expired(), true);
+    check_notification(&results[0], USER + 1, expired(), true);
+    check_claim_state_utils(
+        &id_program,
+        subject,
+        PIECE_ID,
+        Claim {
+            data: ClaimData {
+                status: ClaimStatus::Expired,
+                ..claim.data.clone()
+            },
+            ..claim
+        },
+    );
+    // the exhausted subscriber is unsubscribed
+    check_subscription_state_utils(&id_program, USER + 1, vec![], 0);
+
+    // the claim has already expired
+    expire_claim_utils(
+        &id_program,
+        USER,
+        subject,
+        PIECE_ID,
+        ClaimStatus::Active,
+        Some(IdentityError::InvalidTransition),
     );
+    check_valid_state_utils(&id_program, subject, PIECE_ID + 1, ClaimStatus::Active);
 }
@@ -1,7 +1,193 @@
-use gstd::prelude::*;
-use gtest::{Program, System};
+use gstd::{prelude::*, ActorId};
+use gtest::{Program, RunResult, System};
 use identity::io::*;
 
+pub fn program_actor_id(id_program: &Program) -> ActorId {
+    ActorId::new(
+        id_program
+            .id()
+            .as_ref()
+            .try_into()
+            .expect("IDENTITY: Program id must be 32 bytes long"),
+    )
+}
+
+pub fn claim_signing_bytes(
+    id_program: &Program,
+    subject: PublicKey,
+    issuer: PublicKey,
+    data: &ClaimData,
+    nonce: u64,
+) -> Vec<u8> {
+    ClaimSigningPayload::new(
+        program_actor_id(id_program),
+        subject,
+        issuer,
+        data.clone(),
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn status_change_signing_bytes(
+    id_program: &Program,
+    validator: PublicKey,
+    subject: PublicKey,
+    piece_id: PieceId,
+    status: ClaimStatus,
+    nonce: u64,
+) -> Vec<u8> {
+    StatusChangeSigningPayload::new(
+        program_actor_id(id_program),
+        validator,
+        subject,
+        piece_id,
+        status,
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn key_link_signing_bytes(
+    id_program: &Program,
+    key: PublicKey,
+    actor: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    KeyLinkSigningPayload::new(program_actor_id(id_program), key, actor.into(), nonce)
+        .signing_bytes()
+}
+
+pub fn key_unlink_signing_bytes(
+    id_program: &Program,
+    key: PublicKey,
+    actor: u64,
+    nonce: u64,
+) -> Vec<u8> {
+    KeyUnlinkSigningPayload::new(program_actor_id(id_program), key, actor.into(), nonce)
+        .signing_bytes()
+}
+
+/// Returns the [`Claim::hash`] of the stored claim or zeroes if there's no such claim.
+pub fn claim_hash(id_program: &Program, subject: PublicKey, piece_id: PieceId) -> [u8; 32] {
+    match id_program.meta_state(IdentityStateQuery::Claim(subject, piece_id)) {
+        Ok(IdentityStateReply::Claim(claim)) => claim.map(|claim| claim.hash()).unwrap_or_default(),
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Claim payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn verification_signing_bytes(
+    id_program: &Program,
+    verifier: PublicKey,
+    subject: PublicKey,
+    piece_id: PieceId,
+    statement: &str,
+    nonce: u64,
+) -> Vec<u8> {
+    VerificationSigningPayload::new(
+        program_actor_id(id_program),
+        verifier,
+        subject,
+        piece_id,
+        claim_hash(id_program, subject, piece_id),
+        statement.into(),
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn revocation_signing_bytes(
+    id_program: &Program,
+    issuer: PublicKey,
+    subject: PublicKey,
+    piece_id: PieceId,
+    reason: RevocationReason,
+    nonce: u64,
+) -> Vec<u8> {
+    RevocationSigningPayload::new(
+        program_actor_id(id_program),
+        issuer,
+        subject,
+        piece_id,
+        reason,
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn withdrawal_signing_bytes(
+    id_program: &Program,
+    verifier: PublicKey,
+    subject: PublicKey,
+    piece_id: PieceId,
+    reason: WithdrawalReason,
+    nonce: u64,
+) -> Vec<u8> {
+    WithdrawalSigningPayload::new(
+        program_actor_id(id_program),
+        verifier,
+        subject,
+        piece_id,
+        reason,
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn verification_policy_signing_bytes(
+    id_program: &Program,
+    owner: PublicKey,
+    subject: PublicKey,
+    piece_id: PieceId,
+    policy: VerificationPolicy,
+    nonce: u64,
+) -> Vec<u8> {
+    VerificationPolicySigningPayload::new(
+        program_actor_id(id_program),
+        owner,
+        subject,
+        piece_id,
+        policy,
+        nonce,
+    )
+    .signing_bytes()
+}
+
+pub fn issuer_registration_signing_bytes(
+    id_program: &Program,
+    issuer: PublicKey,
+    metadata: &IssuerMetadata,
+    nonce: u64,
+) -> Vec<u8> {
+    IssuerRegistrationSigningPayload::new(
+        program_actor_id(id_program),
+        issuer,
+        metadata.clone(),
+        nonce,
+    )
+    .signing_bytes()
+}
+
+/// Returns the hash an Ethereum wallet signs with `personal_sign` (EIP-191) for the `message`.
+pub fn ethereum_message_hash(message: &[u8]) -> [u8; 32] {
+    let mut prefixed = format!("\x19Ethereum Signed Message:\n{}", message.len()).into_bytes();
+    prefixed.extend_from_slice(message);
+    sp_core::keccak_256(&prefixed)
+}
+
+/// Returns the issuance date of a claim issued in the current block.
+pub fn issuance_date(sys: &System, asserted: u64) -> IssuanceDate {
+    IssuanceDate {
+        asserted,
+        block_timestamp: sys.block_timestamp(),
+        block_height: sys.block_height(),
+    }
+}
+
 // MESSAGES
 pub fn init_identity(sys: &System, user: u64) -> Program {
     sys.init_logger();
@@ -15,9 +201,10 @@ pub fn issue_claim_utils(
     id_program: &Program,
     user: u64,
     claim: Claim,
+    nonce: u64,
     piece_id: PieceId,
-    should_fail: bool,
-) {
+    error: Option<IdentityError>,
+) -> RunResult {
     let res = id_program.send(
         user,
         IdentityAction::IssueClaim {
@@ -25,120 +212,695 @@ pub fn issue_claim_utils(
             issuer_signature: claim.issuer_signature,
             subject: claim.subject,
             data: claim.data,
+            nonce,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::ClaimIssued {
+            issuer: claim.issuer,
+            subject: claim.subject,
+            piece_id,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn validation_claim_utils(
+    id_program: &Program,
+    user: u64,
+    validator: Authority,
+    subject: PublicKey,
+    piece_id: PieceId,
+    old_status: ClaimStatus,
+    status: ClaimStatus,
+    error: Option<IdentityError>,
+) -> RunResult {
+    let res = id_program.send(
+        user,
+        IdentityAction::ChangeClaimValidationStatus {
+            validator,
+            subject,
+            piece_id,
+            status,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::ClaimValidationChanged {
+            validator: match validator {
+                Authority::Key { key, .. } => Some(key),
+                Authority::Admin => None,
+            },
+            subject,
+            piece_id,
+            old_status,
+            new_status: status,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn verify_claim_utils(
+    id_program: &Program,
+    user: u64,
+    verifier: PublicKey,
+    verifier_signature: Signature,
+    subject: PublicKey,
+    piece_id: PieceId,
+    statement: &str,
+    nonce: u64,
+    error: Option<IdentityError>,
+) -> RunResult {
+    let res = id_program.send(
+        user,
+        IdentityAction::VerifyClaim {
+            verifier,
+            verifier_signature,
+            subject,
+            piece_id,
+            statement: statement.into(),
+            nonce,
         },
     );
 
-    if should_fail {
-        assert!(res.main_failed());
-    } else {
-        assert!(res.contains(&(
-            user,
-            IdentityEvent::ClaimIssued {
-                issuer: claim.issuer,
-                subject: claim.subject,
-                piece_id,
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::VerifiedClaim {
+            verifier,
+            subject,
+            piece_id,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn set_verification_policy_utils(
+    id_program: &Program,
+    user: u64,
+    owner: PublicKey,
+    proof: KeyProof,
+    subject: PublicKey,
+    piece_id: PieceId,
+    policy: VerificationPolicy,
+    error: Option<IdentityError>,
+) -> RunResult {
+    let res = id_program.send(
+        user,
+        IdentityAction::SetVerificationPolicy {
+            owner,
+            proof,
+            subject,
+            piece_id,
+            policy: policy.clone(),
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::VerificationPolicySet {
+            owner,
+            subject,
+            piece_id,
+            policy,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+pub fn check_quorum_event(res: &RunResult, subject: PublicKey, piece_id: PieceId, reached: bool) {
+    let event = IdentityEvent::QuorumReached { subject, piece_id };
+    if res.contains(&(0, event.encode())) != reached {
+        panic!("IDENTITY: Quorum events differ");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn revoke_claim_utils(
+    sys: &System,
+    id_program: &Program,
+    user: u64,
+    issuer: PublicKey,
+    proof: KeyProof,
+    subject: PublicKey,
+    piece_id: PieceId,
+    old_status: ClaimStatus,
+    reason: RevocationReason,
+    error: Option<IdentityError>,
+) -> RunResult {
+    let res = id_program.send(
+        user,
+        IdentityAction::RevokeClaim {
+            issuer,
+            proof,
+            subject,
+            piece_id,
+            reason,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::ClaimRevoked {
+            issuer,
+            subject,
+            piece_id,
+            old_status,
+            reason,
+            timestamp: sys.block_timestamp(),
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn withdraw_verification_utils(
+    sys: &System,
+    id_program: &Program,
+    user: u64,
+    verifier: PublicKey,
+    proof: KeyProof,
+    subject: PublicKey,
+    piece_id: PieceId,
+    reason: WithdrawalReason,
+    error: Option<IdentityError>,
+) -> RunResult {
+    let res = id_program.send(
+        user,
+        IdentityAction::WithdrawVerification {
+            verifier,
+            proof,
+            subject,
+            piece_id,
+            reason,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::VerificationWithdrawn {
+            verifier,
+            subject,
+            piece_id,
+            reason,
+            timestamp: sys.block_timestamp(),
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+    res
+}
+
+pub fn expire_claim_utils(
+    id_program: &Program,
+    user: u64,
+    subject: PublicKey,
+    piece_id: PieceId,
+    old_status: ClaimStatus,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(user, IdentityAction::ExpireClaim { subject, piece_id });
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::ClaimExpired {
+            subject,
+            piece_id,
+            old_status,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn subscribe_utils(
+    id_program: &Program,
+    user: u64,
+    target: SubscriptionTarget,
+    notifications: u32,
+    reserved_notifications: u32,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::Subscribe {
+            target,
+            notifications,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::Subscribed {
+            subscriber: user.into(),
+            target,
+            reserved_notifications,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn unsubscribe_utils(
+    id_program: &Program,
+    user: u64,
+    target: SubscriptionTarget,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(user, IdentityAction::Unsubscribe { target });
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::Unsubscribed {
+            subscriber: user.into(),
+            target,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn check_notification(res: &RunResult, subscriber: u64, event: IdentityEvent, notified: bool) {
+    if res.contains(&(subscriber, event.encode())) != notified {
+        panic!("IDENTITY: Notifications differ");
+    }
+}
+
+pub fn register_schema_utils(
+    id_program: &Program,
+    user: u64,
+    name: &str,
+    attributes: &[&str],
+    hash_algorithm: HashAlgorithm,
+    schema_id: SchemaId,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::RegisterSchema {
+            name: name.into(),
+            attributes: attributes
+                .iter()
+                .map(|attribute| attribute.to_string())
+                .collect(),
+            hash_algorithm,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::SchemaRegistered { schema_id }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn register_policy_utils(
+    id_program: &Program,
+    user: u64,
+    name: &str,
+    schema_id: SchemaId,
+    issuers: Vec<PublicKey>,
+    min_verifiers: u32,
+    max_age: Option<u64>,
+    policy_id: PolicyId,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::RegisterPolicy {
+            name: name.into(),
+            schema_id,
+            issuers,
+            min_verifiers,
+            max_age,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::PolicyRegistered { policy_id }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn query_claim_status_utils(
+    id_program: &Program,
+    user: u64,
+    subject: PublicKey,
+    piece_id: PieceId,
+    status: Option<ClaimStatus>,
+) {
+    let res = id_program.send(user, IdentityAction::QueryClaimStatus { subject, piece_id });
+
+    let reply: Result<IdentityEvent, IdentityError> = Ok(IdentityEvent::ClaimStatusQueried {
+        subject,
+        piece_id,
+        status,
+    });
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn query_check_claim_utils(
+    id_program: &Program,
+    user: u64,
+    subject: PublicKey,
+    piece_id: PieceId,
+    hash: [u8; 32],
+    trusted_only: bool,
+    valid: bool,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::QueryCheckClaim {
+            subject,
+            piece_id,
+            hash,
+            trusted_only,
+        },
+    );
+
+    let reply: Result<IdentityEvent, IdentityError> = Ok(IdentityEvent::ClaimChecked {
+        subject,
+        piece_id,
+        hash,
+        valid,
+    });
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn satisfies_policy_utils(
+    id_program: &Program,
+    user: u64,
+    subject: PublicKey,
+    policy_id: PolicyId,
+    piece_ids: Vec<PieceId>,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::QuerySatisfiesPolicy { subject, policy_id },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::PolicySatisfied {
+            subject,
+            policy_id,
+            piece_ids,
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn register_issuer_utils(
+    id_program: &Program,
+    user: u64,
+    issuer: PublicKey,
+    proof: KeyProof,
+    metadata: IssuerMetadata,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::RegisterIssuer {
+            issuer,
+            proof,
+            metadata,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::IssuerRegistered { issuer }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn set_issuer_trust_utils(
+    id_program: &Program,
+    user: u64,
+    issuer: PublicKey,
+    trusted: bool,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(user, IdentityAction::SetIssuerTrust { issuer, trusted });
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::IssuerTrustChanged { issuer, trusted }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn check_merkle_attribute_utils(
+    id_program: &Program,
+    user: u64,
+    subject: PublicKey,
+    piece_id: PieceId,
+    attribute: &str,
+    leaf: [u8; 32],
+    path: Vec<[u8; 32]>,
+    included: bool,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::CheckMerkleAttribute {
+            subject,
+            piece_id,
+            attribute: attribute.into(),
+            leaf,
+            path,
+        },
+    );
+
+    let reply: Result<IdentityEvent, IdentityError> = Ok(IdentityEvent::MerkleAttributeChecked {
+        subject,
+        piece_id,
+        attribute: attribute.into(),
+        included,
+    });
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn link_key_utils(
+    id_program: &Program,
+    user: u64,
+    key: PublicKey,
+    signature: Signature,
+    nonce: u64,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::LinkKey {
+            key,
+            signature,
+            nonce,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::KeyLinked {
+            key,
+            actor: user.into(),
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+pub fn unlink_key_utils(
+    id_program: &Program,
+    user: u64,
+    key: PublicKey,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(user, IdentityAction::UnlinkKey { key });
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::KeyUnlinked {
+            key,
+            actor: user.into(),
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn unlink_actor_utils(
+    id_program: &Program,
+    user: u64,
+    key: PublicKey,
+    actor: u64,
+    signature: Signature,
+    nonce: u64,
+    error: Option<IdentityError>,
+) {
+    let res = id_program.send(
+        user,
+        IdentityAction::UnlinkActor {
+            key,
+            actor: actor.into(),
+            signature,
+            nonce,
+        },
+    );
+
+    let reply = match error {
+        Some(error) => Err(error),
+        None => Ok(IdentityEvent::KeyUnlinked {
+            key,
+            actor: actor.into(),
+        }),
+    };
+    assert!(res.contains(&(user, reply.encode())));
+}
+
+// META-STATE
+pub fn check_claim_hash_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    hash: [u8; 32],
+    trusted_only: bool,
+    status: bool,
+) {
+    match id_program.meta_state(IdentityStateQuery::CheckClaim(
+        subject,
+        piece_id,
+        hash,
+        trusted_only,
+    )) {
+        Ok(IdentityStateReply::CheckedClaim(_, _, real_status)) => {
+            if real_status != status {
+                panic!("IDENTITY: Checking statuses differ")
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::CheckClaim payload has occurred"
+            )
+        }
+    }
+}
+pub fn check_claim_attribute_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    attribute: &str,
+    hash: [u8; 32],
+    status: bool,
+) {
+    match id_program.meta_state(IdentityStateQuery::CheckClaimAttribute(
+        subject,
+        piece_id,
+        attribute.into(),
+        hash,
+    )) {
+        Ok(IdentityStateReply::CheckedClaimAttribute(_, _, _, real_status)) => {
+            if real_status != status {
+                panic!("IDENTITY: Checking attribute statuses differ")
             }
-            .encode()
-        )));
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::CheckClaimAttribute payload has occurred"
+            )
+        }
     }
 }
 
-pub fn validation_claim_utils(
+pub fn check_presentation_state_utils(
     id_program: &Program,
-    user: u64,
-    validator: PublicKey,
     subject: PublicKey,
     piece_id: PieceId,
+    presentation: AttributePresentation,
     status: bool,
-    should_fail: bool,
 ) {
-    let res = id_program.send(
-        user,
-        IdentityAction::ChangeClaimValidationStatus {
-            validator,
-            subject,
-            piece_id,
-            status,
-        },
-    );
-
-    if should_fail {
-        assert!(res.main_failed());
-    } else {
-        assert!(res.contains(&(
-            user,
-            IdentityEvent::ClaimValidationChanged {
-                validator,
-                subject,
-                piece_id,
-                status,
+    match id_program.meta_state(IdentityStateQuery::CheckPresentation(
+        subject,
+        piece_id,
+        presentation,
+    )) {
+        Ok(IdentityStateReply::CheckedPresentation(_, _, real_status)) => {
+            if real_status != status {
+                panic!("IDENTITY: Checking presentation statuses differ")
             }
-            .encode()
-        )));
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::CheckPresentation payload has occurred"
+            )
+        }
     }
 }
 
-pub fn verify_claim_utils(
+pub fn check_threshold_state_utils(
     id_program: &Program,
-    user: u64,
-    verifier: PublicKey,
-    verifier_signature: Signature,
     subject: PublicKey,
     piece_id: PieceId,
-    should_fail: bool,
+    proof: ThresholdProof,
+    status: bool,
 ) {
-    let res = id_program.send(
-        user,
-        IdentityAction::VerifyClaim {
-            verifier,
-            verifier_signature,
-            subject,
-            piece_id,
-        },
-    );
-
-    if should_fail {
-        assert!(res.main_failed());
-    } else {
-        assert!(res.contains(&(
-            user,
-            IdentityEvent::VerifiedClaim {
-                verifier,
-                subject,
-                piece_id,
+    match id_program.meta_state(IdentityStateQuery::CheckThreshold(subject, piece_id, proof)) {
+        Ok(IdentityStateReply::CheckedThreshold(_, _, real_status)) => {
+            if real_status != status {
+                panic!("IDENTITY: Checking threshold statuses differ")
             }
-            .encode()
-        )));
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::CheckThreshold payload has occurred"
+            )
+        }
     }
 }
 
-// META-STATE
-pub fn check_claim_hash_state_utils(
+pub fn check_merkle_attribute_state_utils(
     id_program: &Program,
     subject: PublicKey,
     piece_id: PieceId,
-    hash: [u8; 32],
+    attribute: &str,
+    leaf: [u8; 32],
+    path: Vec<[u8; 32]>,
     status: bool,
 ) {
-    match id_program.meta_state(IdentityStateQuery::CheckClaim(subject, piece_id, hash)) {
-        Ok(IdentityStateReply::CheckedClaim(_, _, real_status)) => {
+    match id_program.meta_state(IdentityStateQuery::CheckMerkleAttribute(
+        subject,
+        piece_id,
+        attribute.into(),
+        leaf,
+        path,
+    )) {
+        Ok(IdentityStateReply::CheckedMerkleAttribute(_, _, _, real_status)) => {
             if real_status != status {
-                panic!("IDENTITY: Checking statuses differ")
+                panic!("IDENTITY: Checking Merkle attribute statuses differ")
             }
         }
         _ => {
             unreachable!(
-                "Unreachable metastate reply for the IdentityStateQuery::CheckClaim payload has occurred"
+                "Unreachable metastate reply for the IdentityStateQuery::CheckMerkleAttribute payload has occurred"
             )
         }
     }
 }
+
 pub fn check_user_claims_state_utils(
     id_program: &Program,
     subject: PublicKey,
+    trusted_only: bool,
     claims: Vec<(PieceId, Claim)>,
 ) {
-    match id_program.meta_state(IdentityStateQuery::UserClaims(subject)) {
+    match id_program.meta_state(IdentityStateQuery::UserClaims(subject, trusted_only)) {
         Ok(IdentityStateReply::UserClaims(real_claims)) => {
             if real_claims != claims {
                 panic!("IDENTITY: User claims differ")
@@ -192,15 +954,118 @@ pub fn check_verifiers_state_utils(
     }
 }
 
+pub fn check_all_verifiers_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    verifiers: Vec<PublicKey>,
+) {
+    match id_program.meta_state(IdentityStateQuery::AllVerifiers(subject, piece_id)) {
+        Ok(IdentityStateReply::AllVerifiers(real_verifiers)) => {
+            if real_verifiers != verifiers {
+                panic!("IDENTITY: Verifiers differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::AllVerifiers payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_verification_policy_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    policy: Option<VerificationPolicy>,
+) {
+    match id_program.meta_state(IdentityStateQuery::VerificationPolicy(subject, piece_id)) {
+        Ok(IdentityStateReply::VerificationPolicy(real_policy)) => {
+            if real_policy != policy {
+                panic!("IDENTITY: Verification policies differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::VerificationPolicy payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_sufficiently_verified_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    verified: bool,
+) {
+    match id_program.meta_state(IdentityStateQuery::IsSufficientlyVerified(
+        subject, piece_id,
+    )) {
+        Ok(IdentityStateReply::IsSufficientlyVerified(real_verified)) => {
+            if real_verified != verified {
+                panic!("IDENTITY: Sufficient verification statuses differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::IsSufficientlyVerified payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_subscription_state_utils(
+    id_program: &Program,
+    subscriber: u64,
+    targets: Vec<SubscriptionTarget>,
+    reserved_notifications: u32,
+) {
+    match id_program.meta_state(IdentityStateQuery::Subscription(subscriber.into())) {
+        Ok(IdentityStateReply::Subscription(real_targets, real_reserved_notifications)) => {
+            if real_targets != targets || real_reserved_notifications != reserved_notifications {
+                panic!("IDENTITY: Subscriptions differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Subscription payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_withdrawal_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    verifier: PublicKey,
+    withdrawal: Option<Withdrawal>,
+) {
+    match id_program.meta_state(IdentityStateQuery::Withdrawal(subject, piece_id, verifier)) {
+        Ok(IdentityStateReply::Withdrawal(real_withdrawal)) => {
+            if real_withdrawal != withdrawal {
+                panic!("IDENTITY: Withdrawals differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Withdrawal payload has occurred"
+            )
+        }
+    }
+}
+
 pub fn check_date_state_utils(
     id_program: &Program,
     subject: PublicKey,
     piece_id: PieceId,
-    date: u64,
+    date: IssuanceDate,
 ) {
     match id_program.meta_state(IdentityStateQuery::Date(subject, piece_id)) {
         Ok(IdentityStateReply::Date(real_date)) => {
-            if real_date != date {
+            if real_date != Some(date) {
                 panic!("IDENTITY: Dates differ");
             }
         }
@@ -216,11 +1081,11 @@ pub fn check_valid_state_utils(
     id_program: &Program,
     subject: PublicKey,
     piece_id: PieceId,
-    valid: bool,
+    status: ClaimStatus,
 ) {
     match id_program.meta_state(IdentityStateQuery::ValidationStatus(subject, piece_id)) {
-        Ok(IdentityStateReply::ValidationStatus(real_valid)) => {
-            if real_valid != valid {
+        Ok(IdentityStateReply::ValidationStatus(real_status)) => {
+            if real_status != Some(status) {
                 panic!("IDENTITY: Validation status differ");
             }
         }
@@ -231,3 +1096,215 @@ pub fn check_valid_state_utils(
         }
     }
 }
+
+pub fn check_nonce_state_utils(id_program: &Program, pkey: PublicKey, nonce: u64) {
+    match id_program.meta_state(IdentityStateQuery::Nonce(pkey)) {
+        Ok(IdentityStateReply::Nonce(real_nonce)) => {
+            if real_nonce != nonce {
+                panic!("IDENTITY: Nonces differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Nonce payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_key_actors_state_utils(id_program: &Program, pkey: PublicKey, actors: Vec<u64>) {
+    match id_program.meta_state(IdentityStateQuery::KeyActors(pkey)) {
+        Ok(IdentityStateReply::KeyActors(real_actors)) => {
+            if real_actors != actors.into_iter().map(ActorId::from).collect::<Vec<_>>() {
+                panic!("IDENTITY: Key actors differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::KeyActors payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_actor_keys_state_utils(id_program: &Program, actor: u64, keys: Vec<PublicKey>) {
+    match id_program.meta_state(IdentityStateQuery::ActorKeys(actor.into())) {
+        Ok(IdentityStateReply::ActorKeys(real_keys)) => {
+            if real_keys != keys {
+                panic!("IDENTITY: Actor keys differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::ActorKeys payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_revocation_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    revocation: Option<Revocation>,
+) {
+    match id_program.meta_state(IdentityStateQuery::IsRevoked(subject, piece_id)) {
+        Ok(IdentityStateReply::IsRevoked(real_revocation)) => {
+            if real_revocation != revocation {
+                panic!("IDENTITY: Revocations differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::IsRevoked payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_expiring_claims_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    period: u64,
+    claims: Vec<(PieceId, u64)>,
+) {
+    match id_program.meta_state(IdentityStateQuery::ExpiringClaims(subject, period)) {
+        Ok(IdentityStateReply::ExpiringClaims(real_claims)) => {
+            if real_claims != claims {
+                panic!("IDENTITY: Expiring claims differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::ExpiringClaims payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_issuer_state_utils(id_program: &Program, issuer: PublicKey, info: Option<IssuerInfo>) {
+    match id_program.meta_state(IdentityStateQuery::Issuer(issuer)) {
+        Ok(IdentityStateReply::Issuer(real_info)) => {
+            if real_info != info {
+                panic!("IDENTITY: Issuers differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Issuer payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_accreditation_chain_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    piece_id: PieceId,
+    root: PublicKey,
+    max_depth: u32,
+    chain: Option<Vec<PublicKey>>,
+) {
+    match id_program.meta_state(IdentityStateQuery::AccreditationChain(
+        subject, piece_id, root, max_depth,
+    )) {
+        Ok(IdentityStateReply::AccreditationChain(real_chain)) => {
+            if real_chain != chain {
+                panic!("IDENTITY: Accreditation chains differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::AccreditationChain payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_schema_state_utils(id_program: &Program, schema_id: SchemaId, schema: Option<Schema>) {
+    match id_program.meta_state(IdentityStateQuery::Schema(schema_id)) {
+        Ok(IdentityStateReply::Schema(real_schema)) => {
+            if real_schema != schema {
+                panic!("IDENTITY: Schemas differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Schema payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_schemas_state_utils(id_program: &Program, schemas: Vec<Schema>) {
+    match id_program.meta_state(IdentityStateQuery::Schemas) {
+        Ok(IdentityStateReply::Schemas(real_schemas)) => {
+            if real_schemas != schemas {
+                panic!("IDENTITY: Schemas differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Schemas payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_claims_by_schema_state_utils(
+    id_program: &Program,
+    schema_id: SchemaId,
+    claims: Vec<(PieceId, Claim)>,
+) {
+    match id_program.meta_state(IdentityStateQuery::ClaimsBySchema(schema_id)) {
+        Ok(IdentityStateReply::ClaimsBySchema(real_claims)) => {
+            if real_claims != claims {
+                panic!("IDENTITY: Schema claims differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::ClaimsBySchema payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_policy_state_utils(
+    id_program: &Program,
+    policy_id: PolicyId,
+    policy: Option<RelyingPartyPolicy>,
+) {
+    match id_program.meta_state(IdentityStateQuery::Policy(policy_id)) {
+        Ok(IdentityStateReply::Policy(real_policy)) => {
+            if real_policy != policy {
+                panic!("IDENTITY: Policies differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::Policy payload has occurred"
+            )
+        }
+    }
+}
+
+pub fn check_satisfies_policy_state_utils(
+    id_program: &Program,
+    subject: PublicKey,
+    policy_id: PolicyId,
+    piece_ids: Vec<PieceId>,
+) {
+    match id_program.meta_state(IdentityStateQuery::SatisfiesPolicy(subject, policy_id)) {
+        Ok(IdentityStateReply::SatisfiesPolicy(real_piece_ids)) => {
+            if real_piece_ids != piece_ids {
+                panic!("IDENTITY: Claims satisfying the policy differ");
+            }
+        }
+        _ => {
+            unreachable!(
+                "Unreachable metastate reply for the IdentityStateQuery::SatisfiesPolicy payload has occurred"
+            )
+        }
+    }
+}